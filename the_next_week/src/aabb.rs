@@ -125,7 +125,12 @@ impl Aabb {
     }
 
 
-    pub fn hit(&self, r: &Ray, mut ray_t: Interval) -> bool {
+    pub fn hit(&self, r: &Ray, ray_t: Interval) -> bool {
+        self.hit_interval(r, ray_t).is_some()
+    }
+
+    /// 光线在 ray_t 范围内穿过包围盒的那一段，没穿过返回 None
+    pub fn hit_interval(&self, r: &Ray, mut ray_t: Interval) -> Option<Interval> {
         let ray_orig = r.origin();
         let ray_dir = r.direction();
         for axis in 0..3 {
@@ -143,10 +148,10 @@ impl Aabb {
                 ray_t.max = t1;
             }
             if ray_t.min >= ray_t.max {
-                return false;
+                return None;
             }
         }
-        true
+        Some(ray_t)
     }
 
     pub fn longest_axis(&self) -> usize {
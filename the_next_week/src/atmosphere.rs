@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::dvec3::DVec3Ext;
+use crate::enums::PhaseFunction;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::{Anisotropic, Material};
+use crate::random_number_generator::random;
+use crate::ray::Ray;
+
+/// 充满整个场景的大气或雾，作为 Camera::medium 使用，逃逸到背景的光线也会被衰减和散射。
+/// 高度 y 处的密度是 density * exp(-height_falloff * (y - base_height))，height_falloff 为 0 时就是均匀的雾。
+/// 沿光线的光学厚度有解析式，采样碰撞距离和计算透射率都不需要步进
+pub struct Atmosphere {
+    density: f64,
+    height_falloff: f64,
+    base_height: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl Atmosphere {
+    pub fn new(density: f64, albedo: Color, phase: PhaseFunction) -> Atmosphere {
+        Atmosphere {
+            density,
+            height_falloff: 0.0,
+            base_height: 0.0,
+            phase_function: Arc::new(Anisotropic::new_from_solid_color(albedo, phase)),
+        }
+    }
+
+    /// 密度在 base_height 处等于 density，每升高 1 / height_falloff 减少到 1/e
+    pub fn with_height_falloff(mut self, height_falloff: f64, base_height: f64) -> Atmosphere {
+        self.height_falloff = height_falloff;
+        self.base_height = base_height;
+        self
+    }
+
+    /// 光学厚度写成 scale * (1 - exp(-rate * s)) / rate，s 是从 t0 往前走的光线参数。
+    /// 返回 (scale, rate)，scale 是 t0 处单位光线参数上的消光，rate 是它沿光线衰减的速度
+    fn optical_depth_coefficients(&self, r: &Ray, t0: f64) -> (f64, f64) {
+        let height = r.at(t0).y - self.base_height;
+        let scale = self.density * (-self.height_falloff * height).exp() * r.direction().length();
+        (scale, self.height_falloff * r.direction().y)
+    }
+
+    /// 从 t0 开始走 s 的光学厚度
+    fn optical_depth(&self, r: &Ray, t0: f64, s: f64) -> f64 {
+        let (scale, rate) = self.optical_depth_coefficients(r, t0);
+        if scale == 0.0 {
+            return 0.0;
+        }
+        if rate == 0.0 {
+            scale * s
+        } else {
+            // exp_m1 在 rate * s 很小时仍然精确
+            scale * -(-rate * s).exp_m1() / rate
+        }
+    }
+}
+
+impl Hittable for Atmosphere {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.density <= 0.0 {
+            return None;
+        }
+        let t0 = ray_t.min.max(0.0);
+        let (scale, rate) = self.optical_depth_coefficients(r, t0);
+        if scale == 0.0 {
+            return None;
+        }
+
+        // 先按指数分布抽一个光学厚度，再反解出对应的距离。往上走时总的光学厚度有限，可能永远不发生碰撞
+        let target = -(1.0 - random()).ln() / scale;
+        let s = if rate == 0.0 {
+            target
+        } else if rate * target >= 1.0 {
+            return None;
+        } else {
+            -(-rate * target).ln_1p() / rate
+        };
+
+        let hit_t = t0 + s;
+        if hit_t >= ray_t.max {
+            return None;
+        }
+        Some(HitRecord::with_hit_data(
+            hit_t,
+            r.at(hit_t),
+            (0.0, 0.0),
+            r,
+            DVec3::random_unit(), // 随机的法线
+            self.phase_function.clone(),
+        ))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::UNIVERSE
+    }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        if self.density <= 0.0 {
+            return Color::ONE;
+        }
+        let t0 = ray_t.min.max(0.0);
+        if ray_t.max <= t0 {
+            return Color::ONE;
+        }
+        Color::splat((-self.optical_depth(r, t0, ray_t.max - t0)).exp())
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::aabb::Aabb;
+use crate::color::Color;
 use crate::hit_record::HitRecord;
 use crate::hittable::Hittable;
 use crate::hittable_list::HittableList;
@@ -78,4 +79,16 @@ impl Hittable for BvhNode {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        if !self.bbox.hit(r, ray_t) {
+            return Color::ONE;
+        }
+        let left = self.left.transmittance(r, ray_t);
+        // 只有一个物体时左右子树是同一个，不能算两遍
+        if left == Color::ZERO || Arc::ptr_eq(&self.left, &self.right) {
+            return left;
+        }
+        left * self.right.transmittance(r, ray_t)
+    }
 }
@@ -4,6 +4,7 @@ use rayon::prelude::*;
 use std::fs;
 use std::io;
 use std::io::Write;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time;
 
@@ -12,12 +13,16 @@ use crate::color::ColorExt;
 use crate::config;
 use crate::constant;
 use crate::dvec3::DVec3Ext;
+use crate::environment::EnvironmentMap;
+use crate::hit_record::HitRecord;
 use crate::hittable::Hittable;
 use crate::interval::Interval;
+use crate::light::Light;
 use crate::point3::Point3;
 use crate::random_number_generator::{random, random_range};
 
 use crate::ray::Ray;
+use crate::texture_cache::TextureCache;
 
 #[derive(Default)]
 pub struct Camera {
@@ -27,6 +32,9 @@ pub struct Camera {
     pub max_depth: usize,         // Maximum number of ray bounces into scene
     pub background: Color, // Scene background color
     pub enable_gradient_sky: bool,  // sky color
+    pub environment: Option<Arc<EnvironmentMap>>, // 设置后代替 background 和渐变天空，并对它做重要性采样
+    pub lights: Vec<Arc<dyn Light>>, // 点光源、聚光灯、平行光等 delta 光源，每个非镜面交点都会直接采样
+    pub medium: Option<Arc<dyn Hittable>>, // 相机所处、充满整个场景的介质，比如浓雾或者水下，没有边界所以不放进 world
 
     pub vfov: f64, // Vertical view angle (field of view)
     pub lookfrom: Point3,
@@ -42,6 +50,7 @@ pub struct Camera {
     pixel00_loc: Point3,      // Location of pixel 0, 0
     pixel_delta_u: DVec3,     // Offset to pixel to the right
     pixel_delta_v: DVec3,     // Offset to pixel below
+    pixel_spread_angle: f64,  // 一个像素对应的张角，用于估计光线锥在交点处的宽度
 
     defocus_disk_u: DVec3, // Defocus disk horizontal radius
     defocus_disk_v: DVec3, // Defocus disk vertical radius
@@ -56,6 +65,7 @@ impl Camera {
     pub fn render(&mut self, world: &dyn Hittable) -> anyhow::Result<()> {
         let start_time = time::Instant::now();
         self.initialize();
+        TextureCache::global().print_statistics();
 
         let file = fs::File::create("output.ppm")?;
 
@@ -76,7 +86,7 @@ impl Camera {
                     let mut pixel_color = Color::ZERO;
                     for _ in 0..self.samples_per_pixel {
                         let r = self.get_ray(i, j);
-                        pixel_color += self.ray_color(&r, self.max_depth, world);
+                        pixel_color += self.ray_color(&r, self.max_depth, world, false, 0.0);
                     }
                     row.push(self.pixel_samples_scale * pixel_color);
                 }
@@ -126,6 +136,7 @@ impl Camera {
 
         self.pixel_delta_u = viewport_u / self.image_width as f64;
         self.pixel_delta_v = viewport_v / self.image_height as f64;
+        self.pixel_spread_angle = viewport_height / self.focus_dist / self.image_height as f64;
 
         // Calculate the location of the upper left pixel.
         let viewport_upper_left = self.center - self.focus_dist * self.w - viewport_u / 2.0 - viewport_v / 2.0;
@@ -160,31 +171,112 @@ impl Camera {
         self.center + p.x * self.defocus_disk_u + p.y * self.defocus_disk_v
     }
 
-    pub fn ray_color(&self, r: &Ray, depth: usize, world: &dyn Hittable) -> Color {
+    // sampled_environment 表示上一个交点已经对环境光做过直接采样，这条光线再逃逸出去就不能重复计算。
+    // cone_width 是光线锥在光线起点处的宽度，沿路径按像素张角线性增长，用于纹理过滤
+    fn ray_color(&self, r: &Ray, depth: usize, world: &dyn Hittable, sampled_environment: bool, cone_width: f64) -> Color {
         // If we've exceeded the ray bounce limit, no more light is gathered.
         if depth == 0 {
             return Color::ZERO;
         }
 
-        let Some(rec) = world.hit(r, Interval::new(constant::RAY_MIN_DISTANCE, f64::INFINITY)) else {
-            return if self.enable_gradient_sky {
-                // 没击中，背景色，这里可以理解成天空的颜色
-                let unit_direction = r.direction().normalize();
-                let a = 0.5 * (unit_direction.y + 1.0);
-                (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * config::SKY_GRADIENT
+        let Some(mut rec) = self.hit(r, world) else {
+            return if sampled_environment {
+                Color::ZERO
             } else {
-                self.background
+                self.background_color(r)
             };
         };
+
+        // 斜着看表面时覆盖的范围更大
+        let hit_cone_width = cone_width + self.pixel_spread_angle * rec.t * r.direction().length();
+        let cos_theta = r.direction().normalize().dot(rec.unit_normal).abs();
+        rec.footprint = hit_cone_width / cos_theta.max(constant::FOOTPRINT_MIN_COS);
         
-        let color_from_emission_opt = rec.mat.emitted(rec.u, rec.v, rec.p);
-        
+        let color_from_emission_opt = rec.mat.emitted(r, &rec);
+
+        let color_from_lights = self.sample_lights(r, &rec, world);
+        let color_from_environment_opt = self.sample_environment(r, &rec, world);
+
         // 如果能散射，计算散射颜色
-        if let Some((attenuation, scattered)) = rec.mat.scatter(r, &rec) {
-            let color_from_scatter = attenuation * self.ray_color(&scattered, depth - 1, world);
-            return color_from_emission_opt.unwrap_or(Color::ZERO) + color_from_scatter;
+        if let Some(srec) = rec.mat.scatter(r, &rec) {
+            let skip_environment = color_from_environment_opt.is_some() && !srec.is_specular;
+            let color_from_scatter = srec.attenuation * self.ray_color(&srec.scattered, depth - 1, world, skip_environment, hit_cone_width);
+            return color_from_emission_opt.unwrap_or(Color::ZERO)
+                + color_from_lights
+                + color_from_environment_opt.unwrap_or(Color::ZERO)
+                + color_from_scatter;
         }
         // 不能散射，只返回发射颜色（如果有）
-        color_from_emission_opt.unwrap_or(Color::ZERO)
+        color_from_emission_opt.unwrap_or(Color::ZERO) + color_from_lights + color_from_environment_opt.unwrap_or(Color::ZERO)
+    }
+
+    // 没击中任何物体时的颜色，这里可以理解成天空的颜色
+    fn background_color(&self, r: &Ray) -> Color {
+        if let Some(environment) = &self.environment {
+            environment.value(r.direction())
+        } else if self.enable_gradient_sky {
+            let unit_direction = r.direction().normalize();
+            let a = 0.5 * (unit_direction.y + 1.0);
+            (1.0 - a) * Color::new(1.0, 1.0, 1.0) + a * config::SKY_GRADIENT
+        } else {
+            self.background
+        }
+    }
+
+    // world 和相机所处介质中最近的交点
+    fn hit(&self, r: &Ray, world: &dyn Hittable) -> Option<HitRecord> {
+        let world_rec = world.hit(r, Interval::new(constant::RAY_MIN_DISTANCE, f64::INFINITY));
+        let Some(medium) = &self.medium else {
+            return world_rec;
+        };
+        let t_max = world_rec.as_ref().map_or(f64::INFINITY, |rec| rec.t);
+        medium.hit(r, Interval::new(constant::RAY_MIN_DISTANCE, t_max)).or(world_rec)
+    }
+
+    // 阴影光线在 ray_t 范围内穿过 world 和相机所处介质后剩下的比例
+    fn transmittance(&self, r: &Ray, ray_t: Interval, world: &dyn Hittable) -> Color {
+        let transmittance = world.transmittance(r, ray_t);
+        match &self.medium {
+            Some(medium) if transmittance != Color::ZERO => transmittance * medium.transmittance(r, ray_t),
+            _ => transmittance,
+        }
+    }
+
+    // 按环境贴图的亮度分布采样一个方向发阴影光线。材质不支持 eval 时返回 None，表示没有做直接采样
+    fn sample_environment(&self, r: &Ray, rec: &HitRecord, world: &dyn Hittable) -> Option<Color> {
+        let environment = self.environment.as_ref()?;
+        let (direction, pdf) = environment.sample();
+        let f = rec.mat.eval(r, rec, direction)?;
+        if f == Color::ZERO || pdf <= 0.0 {
+            return Some(Color::ZERO);
+        }
+        let shadow_ray = Ray::new_with_time(rec.p, direction, r.time());
+        let transmittance = self.transmittance(&shadow_ray, Interval::new(constant::RAY_MIN_DISTANCE, f64::INFINITY), world);
+        if transmittance == Color::ZERO {
+            return Some(Color::ZERO);
+        }
+        Some(f * transmittance * environment.sampled_value(direction) / pdf)
+    }
+
+    // 对每个 delta 光源发一条阴影光线，按材质的 BRDF 和沿途的透射率累加直接光照
+    fn sample_lights(&self, r: &Ray, rec: &HitRecord, world: &dyn Hittable) -> Color {
+        let mut color = Color::ZERO;
+        for light in &self.lights {
+            let Some(sample) = light.sample(rec.p) else {
+                continue;
+            };
+            let Some(f) = rec.mat.eval(r, rec, sample.direction) else {
+                // 镜面材质，后面的光源也不用看了
+                return Color::ZERO;
+            };
+            if f == Color::ZERO {
+                continue;
+            }
+            let shadow_ray = Ray::new_with_time(rec.p, sample.direction, r.time());
+            let shadow_t = Interval::new(constant::RAY_MIN_DISTANCE, sample.distance - constant::RAY_MIN_DISTANCE);
+            // 被介质部分遮挡时按透射率衰减
+            color += f * self.transmittance(&shadow_ray, shadow_t, world) * sample.radiance;
+        }
+        color
     }
 }
@@ -2,6 +2,9 @@ use crate::color::Color;
 use crate::point3::Point3;
 use glam::DVec3;
 
+pub const AMBIENT_FOG_DENSITY: Option<f64> = None; // 相机和整个房间都笼罩在里面的薄雾，比如 Some(0.0005)
+pub const AMBIENT_FOG_ALBEDO: Color = Color::new(0.9, 0.9, 0.9);
+
 pub const ASPECT_RATIO: f64 = 1.0;
 pub const IMAGE_WIDTH: usize = 600;
 pub const SAMPLES_PER_PIXEL: usize = 200; // Count of random samples for each pixel
@@ -0,0 +1,28 @@
+use crate::color::Color;
+use crate::point3::Point3;
+use glam::DVec3;
+
+pub const POINT_LIGHT_POSITION: Point3 = Point3::new(-3.0, 4.0, 2.0);
+pub const POINT_LIGHT_INTENSITY: Color = Color::new(20.0, 16.0, 12.0);
+pub const SPOT_LIGHT_POSITION: Point3 = Point3::new(3.0, 5.0, 3.0);
+pub const SPOT_LIGHT_TARGET: Point3 = Point3::new(2.0, 0.0, 0.0);
+pub const SPOT_LIGHT_INTENSITY: Color = Color::new(10.0, 20.0, 40.0);
+pub const SPOT_CONE_ANGLE: f64 = 20.0;
+pub const SPOT_FALLOFF_ANGLE: f64 = 12.0;
+pub const SUN_DIRECTION: DVec3 = DVec3::new(-1.0, -2.0, -1.0);
+pub const SUN_IRRADIANCE: Color = Color::new(0.8, 0.8, 0.75);
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 100; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::new(0.02, 0.02, 0.03);
+pub const ENABLE_GRADIENT_SKY: bool = false;
+
+pub const V_FOV: f64 = 30.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 3.0, 12.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 1.0, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
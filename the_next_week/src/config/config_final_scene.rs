@@ -1,9 +1,17 @@
 use crate::color::Color;
+use crate::enums::NoiseType;
 use crate::point3::Point3;
 use glam::DVec3;
 
+pub const NOISE_TYPE: NoiseType = NoiseType::TurbulenceMarble;
+pub const NOISE_SEED: u64 = 42;
 pub const INPUT_POINT_SCALE: f64 = 0.2;
 
+pub const ATMOSPHERE_DENSITY: f64 = 0.0001;
+pub const ATMOSPHERE_ALBEDO: Color = Color::new(1.0, 1.0, 1.0);
+pub const ATMOSPHERE_HEIGHT_FALLOFF: f64 = 0.0; // 大于 0 时雾越往上越淡，比如 0.005
+pub const ATMOSPHERE_BASE_HEIGHT: f64 = 0.0;
+
 pub const ASPECT_RATIO: f64 = 1.0;
 
 pub const IMAGE_WIDTH_HD: usize = 800;
@@ -0,0 +1,43 @@
+use crate::color::Color;
+use crate::enums::PhaseFunction;
+use crate::point3::Point3;
+use glam::DVec3;
+
+pub const CLOUD_GRID_FILE: Option<&str> = None; // 外部密度网格文件，不设置时用湍流噪声生成
+pub const CLOUD_RESOLUTION: usize = 64;
+pub const CLOUD_NOISE_SCALE: f64 = 4.0;
+pub const CLOUD_SEED: u64 = 3;
+pub const CLOUD_DENSITY: f64 = 4.0;
+pub const CLOUD_ALBEDO: Color = Color::new(0.95, 0.95, 0.95);
+pub const CLOUD_PHASE: PhaseFunction = PhaseFunction::DoubleHenyeyGreenstein { forward: 0.8, backward: -0.3, weight: 0.8 }; // 云的强前向散射加一点背光的银边
+pub const SMOKE_RESOLUTION: usize = 48;
+pub const SMOKE_NOISE_SCALE: f64 = 6.0;
+pub const SMOKE_SEED: u64 = 5;
+pub const SMOKE_DENSITY: f64 = 6.0;
+pub const SMOKE_ALBEDO: Color = Color::new(0.4, 0.4, 0.4);
+pub const SMOKE_PHASE: PhaseFunction = PhaseFunction::HenyeyGreenstein(0.3);
+pub const HAZE_DENSITY: f64 = 0.02;
+pub const HAZE_ALBEDO: Color = Color::new(0.9, 0.9, 0.9);
+pub const HAZE_PHASE: PhaseFunction = PhaseFunction::HenyeyGreenstein(0.7);
+pub const MILK_SIGMA_A: Color = Color::new(0.0014, 0.0025, 0.0142); // 脱脂牛奶，单位 1/mm（Jensen 2001）
+pub const MILK_SIGMA_S: Color = Color::new(0.70, 1.22, 1.90);
+pub const MILK_UNITS_PER_MM: f64 = 1.0; // 场景里 1 个单位相当于 1 mm
+pub const MILK_PHASE: PhaseFunction = PhaseFunction::HenyeyGreenstein(0.7);
+pub const MILK_REFRACTION_INDEX: f64 = 1.35;
+pub const SUN_DIRECTION: DVec3 = DVec3::new(-1.0, -1.5, -0.5);
+pub const SUN_IRRADIANCE: Color = Color::new(2.0, 1.9, 1.7);
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 100; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::new(0.70, 0.80, 1.00);
+pub const ENABLE_GRADIENT_SKY: bool = true;
+
+pub const V_FOV: f64 = 30.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 2.5, 12.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 1.8, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
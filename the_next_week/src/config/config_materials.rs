@@ -0,0 +1,43 @@
+use crate::color::Color;
+use crate::point3::Point3;
+use glam::{DVec2, DVec3};
+
+pub const ROUGHNESS_SIGMA: f64 = 30.0; // Oren–Nayar 粗糙度，角度制
+pub const PAPER_TRANSMITTANCE: f64 = 0.6;
+pub const COAT_REFRACTION_INDEX: f64 = 1.5;
+pub const VARNISH_ROUGHNESS: f64 = 0.05;
+pub const VARNISH_THICKNESS: f64 = 0.02;
+pub const VARNISH_ABSORPTION: Color = Color::new(0.5, 1.5, 4.0); // 偏黄的清漆
+pub const MIX_MASK_SCALE: f64 = 4.0;
+pub const MIX_MASK_SEED: u64 = 7;
+pub const FENCE_TILING: DVec2 = DVec2::new(16.0, 6.0); // 栅栏 8 x 3 的 Quad 上平铺的次数
+pub const FENCE_ROTATION: f64 = 45.0;
+pub const FENCE_IMAGE: Option<&str> = None; // 带 alpha 通道的栅栏贴图，不设置时使用程序生成的棋盘格
+pub const BUMP_NOISE_SCALE: f64 = 8.0; // 凹凸噪声的频率，越大越细碎
+pub const BUMP_NOISE_SEED: u64 = 11;
+pub const GRANITE_SCALE: f64 = 6.0;
+pub const GRANITE_SEED: u64 = 13;
+pub const BUMP_STRENGTH: f64 = 0.02;
+pub const WAX_MEAN_FREE_PATH: f64 = 0.05;
+pub const WAX_ALBEDO: Color = Color::new(0.99, 0.95, 0.85);
+pub const WAX_REFRACTION_INDEX: f64 = 1.4;
+pub const CEILING_LIGHT_POWER: f64 = 250.0; // 瓦
+pub const CEILING_LIGHT_EXPONENT: f64 = 1.0;
+pub const SPOT_LIGHT_POWER: f64 = 40.0;
+pub const SPOT_CONE_ANGLE: f64 = 25.0;
+pub const SPOT_FALLOFF_ANGLE: f64 = 15.0;
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 200; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::new(0.05, 0.05, 0.08);
+pub const ENABLE_GRADIENT_SKY: bool = false;
+
+pub const V_FOV: f64 = 30.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 3.0, 12.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 1.0, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
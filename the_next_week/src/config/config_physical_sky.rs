@@ -0,0 +1,26 @@
+use crate::color::Color;
+use crate::point3::Point3;
+use glam::DVec3;
+
+pub const SUN_ELEVATION: f64 = 25.0; // 角度制
+pub const SUN_AZIMUTH: f64 = 60.0;
+pub const TURBIDITY: f64 = 3.0;
+pub const SUN_ANGULAR_DIAMETER: f64 = 1.5; // 比真实的 0.53° 大一些，金属球上的倒影更明显
+pub const GROUND_ALBEDO: Color = Color::new(0.3, 0.3, 0.3);
+pub const SKY_MAP_WIDTH: usize = 512; // 烘焙天空环境贴图的分辨率
+pub const SKY_MAP_HEIGHT: usize = 256;
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 100; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::ZERO;
+pub const ENABLE_GRADIENT_SKY: bool = false;
+
+pub const V_FOV: f64 = 30.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 2.0, 12.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 1.5, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
@@ -1,7 +1,10 @@
 use crate::color::Color;
+use crate::enums::NoiseType;
 use crate::point3::Point3;
 use glam::DVec3;
 
+pub const NOISE_TYPE: NoiseType = NoiseType::TurbulenceMarble;
+pub const NOISE_SEED: u64 = 42;
 pub const INPUT_POINT_SCALE: f64 = 4.0;
 
 pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
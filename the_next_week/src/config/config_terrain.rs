@@ -0,0 +1,30 @@
+use crate::color::Color;
+use crate::point3::Point3;
+use glam::DVec3;
+
+pub const TERRAIN_HEIGHT_IMAGE: Option<&str> = None; // 灰度高度图，为 None 时用噪声生成
+pub const TERRAIN_COLOR_IMAGE: Option<&str> = None; // 贴在地形上的颜色图，为 None 时用纯色
+pub const TERRAIN_RESOLUTION: usize = 512; // 用噪声生成时每个方向的网格点数
+pub const TERRAIN_NOISE_SCALE: f64 = 0.05;
+pub const TERRAIN_NOISE_SEED: u64 = 7;
+pub const TERRAIN_ORIGIN: Point3 = Point3::new(-20.0, 0.0, -20.0);
+pub const TERRAIN_SIZE: DVec3 = DVec3::new(40.0, 8.0, 40.0); // x、z 方向的范围和最大高度
+pub const WATER_LEVEL: f64 = 3.3;
+
+pub const SUN_DIRECTION: DVec3 = DVec3::new(-1.0, -0.6, -0.4); // 光线前进的方向
+pub const SUN_IRRADIANCE: Color = Color::new(2.0, 1.9, 1.7);
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 64; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::new(0.70, 0.80, 1.00);
+pub const ENABLE_GRADIENT_SKY: bool = true;
+
+pub const V_FOV: f64 = 40.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 18.0, 34.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 2.0, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
@@ -0,0 +1,24 @@
+use crate::color::Color;
+use crate::point3::Point3;
+use glam::{DVec2, DVec3};
+
+pub const TEXTURE_IMAGE: &str = "earthmap.jpg";
+pub const WRAP_TILING: DVec2 = DVec2::new(2.0, 2.0); // 上排每块 Quad 上贴图重复的次数
+pub const BORDER_COLOR: Color = Color::new(0.8, 0.2, 0.1);
+pub const CROP_SCALE: DVec2 = DVec2::new(0.04, 0.04); // 下排放大显示的局部占整张图的比例
+pub const CROP_OFFSET: DVec2 = DVec2::new(0.255, 0.625);
+
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+pub const IMAGE_WIDTH: usize = 400;
+pub const SAMPLES_PER_PIXEL: usize = 64; // Count of random samples for each pixel
+pub const MAX_DEPTH: usize = 50; // Maximum number of ray bounces into scene
+pub const BACKGROUND: Color = Color::new(0.70, 0.80, 1.00);
+pub const ENABLE_GRADIENT_SKY: bool = true;
+
+pub const V_FOV: f64 = 30.0;
+pub const LOOKFROM: Point3 = Point3::new(0.0, 0.0, 12.0);
+pub const LOOKAT: Point3 = Point3::new(0.0, 0.0, 0.0);
+pub const V_UP: DVec3 = Point3::new(0.0, 1.0, 0.0);
+
+pub const DEFOCUS_ANGLE: f64 = 0.0;
+pub const FOCUS_DIST: f64 = 10.0;
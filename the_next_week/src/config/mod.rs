@@ -1,24 +1,34 @@
 use crate::color::Color;
-use crate::enums::NoiseType;
 use crate::enums::Scene;
 
 pub const SKY_GRADIENT: Color = Color::new(0.5, 0.7, 1.0);
 pub const ENABLE_BVH: bool = true;
+pub const LAZY_TEXTURE_LOADING: bool = false; // 图片纹理在第一次被采样时才解码
 
 pub const TARGET_SCENE: Scene = Scene::FinalSceneHD;
 
 // perlin noise 相关全局设置
-pub const NOISE_TYPE: NoiseType = NoiseType::TurbulenceMarble;
 pub const HERMITE_CUBIC_SMOOTHED: bool = true;
-pub const TURBULENCE_DEPTH: usize = 7;
 
 
 pub mod config_bouncing_spheres;
 pub mod config_checkered_spheres;
 pub mod config_cornell_box;
 pub mod config_cornell_smoke;
+pub mod config_csg;
+pub mod config_delta_lights;
 pub mod config_earth;
+pub mod config_environment;
 pub mod config_final_scene;
+pub mod config_heterogeneous_media;
+pub mod config_materials;
 pub mod config_perlin_spheres;
+pub mod config_physical_sky;
+pub mod config_primitives;
+pub mod config_procedural;
+pub mod config_sdf;
 pub mod config_shapes;
-pub mod config_simple_light;
\ No newline at end of file
+pub mod config_simple_light;
+pub mod config_subdivision;
+pub mod config_terrain;
+pub mod config_texture_sampling;
\ No newline at end of file
@@ -5,6 +5,31 @@ pub const RAY_MIN_DISTANCE: f64 = 0.0001; //t_min 如果是 0，由于浮点精
 
 pub const NEAR_ZERO_THRESHOLD: f64 = 1e-8;
 
+pub const BUMP_DELTA: f64 = 0.0005; // 凹凸贴图在纹理坐标上做有限差分的步长
+
+pub const SUBSURFACE_MAX_STEPS: usize = 256; // 次表面随机游走的最大散射次数
+
+pub const EMISSION_INTEGRATION_STEPS: usize = 1024; // 功率换算辐射亮度时数值积分的步数
+
+// Preetham 天空模型的亮度单位是 kcd/m²，缩放到和场景里其它光源差不多的量级
+pub const SKY_RADIANCE_SCALE: f64 = 0.05;
+pub const SUN_IRRADIANCE_SCALE: f64 = 3.0;
+pub const SKY_HORIZON_COS: f64 = 0.01; // 贴近地平线时 Perez 公式里的 1 / cosθ 会发散
+pub const SUN_ANGULAR_DIAMETER: f64 = 0.53; // 太阳圆盘的默认张角，角度制
+pub const SUN_DISK_SUPERSAMPLES: usize = 8; // 烘焙太阳圆盘时每个像素每个方向取点的数量
+
+pub const FOOTPRINT_MIN_COS: f64 = 0.1; // 掠射角时限制光线锥在表面上的拉伸倍数
+
+pub const TRANSMITTANCE_ROULETTE_THRESHOLD: f64 = 0.1; // ratio tracking 的透射率低于它时开始轮盘赌
+
+pub const SDF_MAX_STEPS: usize = 512; // sphere tracing 的最大步数，掠过表面的光线走完还没碰到就算没打中
+pub const SDF_HIT_EPSILON: f64 = 0.0001; // 距离小于它就认为到达表面
+pub const SDF_NORMAL_EPSILON: f64 = 0.0001; // 有限差分求法线的步长
+
+pub const NOISE_OCTAVES: usize = 7; // 噪声纹理默认的分形层数
+pub const NOISE_LACUNARITY: f64 = 2.0;
+pub const NOISE_GAIN: f64 = 0.5;
+
 pub const PERLIN_POINT_COUNT: usize = 256;
 
 pub const MINIMUM_AABB_THICKNESS: f64 = 0.0001;
\ No newline at end of file
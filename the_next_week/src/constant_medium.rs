@@ -10,78 +10,198 @@ use crate::dvec3::DVec3Ext;
 use crate::hittable::Hittable;
 use crate::hit_record::HitRecord;
 use crate::interval::Interval;
-use crate::material::{Isotropic, Material};
+use crate::enums::PhaseFunction;
+use crate::material::{Anisotropic, Isotropic, Material, ScatterRecord};
+use crate::random_number_generator::random;
 use crate::ray::Ray;
 use crate::texture::Texture;
 
 pub struct ConstantMedium {
-    boundary: Arc<dyn Hittable>,
-    neg_inv_density: f64,
+    boundary: Option<Arc<dyn Hittable>>, // None 表示没有边界、充满整个空间
+    neg_inv_density: f64,                // 各通道消光系数的最大值（majorant）的负倒数
+    sigma_t: Color,                      // 各通道的消光系数 sigma_a + sigma_s
     phase_function: Arc<dyn Material>,
+    spectral: Option<SpectralTracking>, // 各通道消光系数不同时才有
+}
+
+/// 虚碰撞的概率和各通道的权重，以及真实散射的权重和相函数
+struct SpectralTracking {
+    p_null: f64,
+    null_weight: Color,
+    scatter_weight: Color,
+    phase: PhaseFunction,
 }
 
 impl ConstantMedium {
     pub fn new_from_solid_color(boundary: Arc<dyn Hittable>, density: f64, albedo: Color) -> ConstantMedium {
         ConstantMedium {
-            boundary,
+            boundary: Some(boundary),
             neg_inv_density: -1.0 / density,
+            sigma_t: Color::splat(density),
             phase_function: Arc::new(Isotropic::new_from_solid_color(albedo)),
+            spectral: None,
         }
     }
 
     pub fn new_from_texture(boundary: Arc<dyn Hittable>, density: f64, tex: Arc<dyn Texture>) -> ConstantMedium {
         ConstantMedium {
-            boundary,
+            boundary: Some(boundary),
             neg_inv_density: -1.0 / density,
+            sigma_t: Color::splat(density),
             phase_function: Arc::new(Isotropic::new_from_texture(tex)),
+            spectral: None,
         }
     }
-}
 
-impl Hittable for ConstantMedium {
-    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
-        // 进入
-        let Some(mut rec1) = self.boundary.hit(r, Interval::UNIVERSE) else {
-            return None;
-        };
-        // 离开
-        let Some(mut rec2) = self.boundary.hit(r, Interval::new(rec1.t + constant::RAY_MIN_DISTANCE, f64::INFINITY)) else {
-            return None;
-        };
-
-        if rec1.t < ray_t.min {
-            rec1.t = ray_t.min;
+    pub fn new_with_phase(boundary: Arc<dyn Hittable>, density: f64, albedo: Color, phase: PhaseFunction) -> ConstantMedium {
+        ConstantMedium {
+            boundary: Some(boundary),
+            neg_inv_density: -1.0 / density,
+            sigma_t: Color::splat(density),
+            phase_function: Arc::new(Anisotropic::new_from_solid_color(albedo, phase)),
+            spectral: None,
         }
-        if rec2.t > ray_t.max {
-            rec2.t = ray_t.max;
+    }
+
+    /// 充满整个空间的介质，包围盒是无穷大，不能放进 BVH，只能作为 Camera::medium 使用
+    pub fn new_unbounded(density: f64, albedo: Color, phase: PhaseFunction) -> ConstantMedium {
+        ConstantMedium {
+            boundary: None,
+            neg_inv_density: -1.0 / density,
+            sigma_t: Color::splat(density),
+            phase_function: Arc::new(Anisotropic::new_from_solid_color(albedo, phase)),
+            spectral: None,
         }
-        if rec1.t >= rec2.t {
-            return None;
+    }
+
+    /// 每个通道分别给出吸收系数和散射系数。按最大的消光系数采样碰撞距离，
+    /// 每次碰撞以固定概率当作散射或者虚碰撞（光线原样穿过），两者都带上各通道的权重，
+    /// 期望和每个通道单独采样一致（spectral tracking）。虚碰撞在 hit 里面直接处理，不占用反弹次数。
+    /// 各通道相同时退化为普通的均匀介质
+    pub fn new_chromatic(boundary: Arc<dyn Hittable>, sigma_a: Color, sigma_s: Color, phase: PhaseFunction) -> ConstantMedium {
+        let sigma_t = sigma_a + sigma_s;
+        let majorant = sigma_t.max_element();
+        let sigma_n = Color::splat(majorant) - sigma_t;
+
+        // 按各通道的平均值分配散射和虚碰撞的概率，吸收体现在权重里
+        let (mean_s, mean_n) = (sigma_s.element_sum() / 3.0, sigma_n.element_sum() / 3.0);
+        let p_scatter = if mean_s + mean_n > 0.0 { mean_s / (mean_s + mean_n) } else { 0.0 };
+        let scatter_weight = if p_scatter > 0.0 { sigma_s / (majorant * p_scatter) } else { Color::ZERO };
+        let spectral = (sigma_n != Color::ZERO).then(|| SpectralTracking {
+            p_null: 1.0 - p_scatter,
+            null_weight: sigma_n / (majorant * (1.0 - p_scatter)),
+            scatter_weight,
+            phase,
+        });
+
+        ConstantMedium {
+            boundary: Some(boundary),
+            neg_inv_density: -1.0 / majorant,
+            sigma_t,
+            phase_function: Arc::new(Anisotropic::new_from_solid_color(scatter_weight, phase)),
+            spectral,
         }
-        if rec1.t < 0.0 {
-            rec1.t = 0.0;
+    }
+
+    fn inside_intervals(&self, r: &Ray, ray_t: Interval) -> Vec<Interval> {
+        match &self.boundary {
+            Some(boundary) => boundary_intervals(boundary.as_ref(), r, ray_t),
+            None => {
+                let inside = Interval::new(ray_t.min.max(0.0), ray_t.max);
+                if inside.size() > 0.0 { vec![inside] } else { Vec::new() }
+            }
         }
+    }
+
+    /// 遇到虚碰撞只把权重乘起来，继续采样下一个碰撞距离，直到真实散射或者走出介质。
+    /// 走出介质时权重不是 1，就在出口处返回一个直接穿过的交点，把权重乘到后面的路径上。
+    /// 出口可能和容器的表面重合（比如装牛奶的玻璃球），所以稍微提前一点，继续前进的光线还能打到那个表面
+    fn spectral_hit(&self, r: &Ray, ray_t: Interval, spectral: &SpectralTracking) -> Option<HitRecord> {
         let ray_length = r.direction().length();
-        let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
-        let hit_distance = self.neg_inv_density * rand::random::<f64>().ln();
-        if hit_distance > distance_inside_boundary {
-            return None;
+        let mut weight = Color::ONE;
+        let mut exit = None;
+        for inside in self.inside_intervals(r, ray_t) {
+            let mut t = inside.min;
+            loop {
+                t += self.neg_inv_density * random().ln() / ray_length;
+                if t >= inside.max {
+                    break;
+                }
+                if random() < spectral.p_null {
+                    weight *= spectral.null_weight;
+                    continue;
+                }
+                let albedo = weight * spectral.scatter_weight;
+                return Some(medium_hit_record(r, t, Arc::new(Anisotropic::new_from_solid_color(albedo, spectral.phase))));
+            }
+            exit = Some(inside);
         }
 
-        let hit_t = rec1.t + hit_distance / ray_length;
-        Some(HitRecord::with_hit_data(
-            hit_t,
-            r.at(hit_t),
-            (0.0, 0.0),
-            r,
-            DVec3::random_unit(), // 随机的法线
-            self.phase_function.clone(),
-        ))
+        let exit = exit.filter(|_| weight != Color::ONE)?;
+        let t = (exit.max - 2.0 * constant::RAY_MIN_DISTANCE).max(exit.min);
+        Some(medium_hit_record(r, t, Arc::new(Transmitted { weight })))
+    }
+}
 
+/// 介质里的碰撞点没有表面，法线随机
+fn medium_hit_record(r: &Ray, t: f64, mat: Arc<dyn Material>) -> HitRecord {
+    HitRecord::with_hit_data(t, r.at(t), (0.0, 0.0), r, DVec3::random_unit(), mat)
+}
+
+/// 光线在 boundary 内部、并且落在 ray_t 里的所有区间，按 t 从小到大排列。
+/// t < 0 的部分在光线起点后面，不算
+pub fn boundary_intervals(boundary: &dyn Hittable, r: &Ray, ray_t: Interval) -> Vec<Interval> {
+    let lower = ray_t.min.max(0.0);
+    boundary
+        .spans(r, ray_t.max)
+        .into_iter()
+        .map(|span| Interval::new(span.enter.t.max(lower), span.exit.t.min(ray_t.max)))
+        .filter(|inside| inside.min < inside.max)
+        .collect()
+}
 
+impl Hittable for ConstantMedium {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if let Some(spectral) = &self.spectral {
+            return self.spectral_hit(r, ray_t, spectral);
+        }
+        let ray_length = r.direction().length();
+        let mut hit_distance = self.neg_inv_density * random().ln();
+        // 指数分布没有记忆，依次扣掉每一段的长度即可
+        for inside in self.inside_intervals(r, ray_t) {
+            let distance_inside_boundary = inside.size() * ray_length;
+            if hit_distance <= distance_inside_boundary {
+                let hit_t = inside.min + hit_distance / ray_length;
+                return Some(medium_hit_record(r, hit_t, self.phase_function.clone()));
+            }
+            hit_distance -= distance_inside_boundary;
+        }
+        None
     }
 
     fn bounding_box(&self) -> Aabb {
-        self.boundary.bounding_box()
+        match &self.boundary {
+            Some(boundary) => boundary.bounding_box(),
+            None => Aabb::UNIVERSE,
+        }
+    }
+
+    /// 均匀介质的透射率有解析解 exp(-sigma_t * distance)，每个通道分别计算
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        let ray_length = r.direction().length();
+        let distance_inside_boundary: f64 = self.inside_intervals(r, ray_t).iter().map(|inside| inside.size() * ray_length).sum();
+        (-self.sigma_t * distance_inside_boundary).exp()
+    }
+}
+
+/// 走出介质时带出虚碰撞累积的权重：光线方向不变继续往前走，只乘上各通道的权重
+struct Transmitted {
+    weight: Color,
+}
+
+impl Material for Transmitted {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let continued = Ray::new_with_time(rec.p, r_in.direction(), r_in.time());
+        Some(ScatterRecord::specular(self.weight, continued))
     }
-}
\ No newline at end of file
+}
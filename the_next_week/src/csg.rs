@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::enums::CsgOperation;
+use crate::hit_record::HitRecord;
+use crate::hittable::{Hittable, Span};
+use crate::interval::Interval;
+use crate::point3::Point3;
+use crate::ray::Ray;
+
+impl CsgOperation {
+    fn contains(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// 用布尔运算组合两个封闭物体，比如两个球求交得到透镜、立方体减去球得到挖空的盒子。
+/// 两边都通过 Hittable::spans 给出光线在内部的区间，逐个交点扫描，运算结果在内外之间切换的交点就是新的表面。
+/// Csg 本身也是封闭的，可以继续嵌套
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+    bbox: Aabb,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Arc<dyn Hittable>, right: Arc<dyn Hittable>) -> Csg {
+        let (left_box, right_box) = (left.bounding_box(), right.bounding_box());
+        let bbox = match operation {
+            CsgOperation::Union => Aabb::new_from_merged(left_box, right_box),
+            CsgOperation::Intersection => {
+                let min = Point3::new(left_box[0].min.max(right_box[0].min), left_box[1].min.max(right_box[1].min), left_box[2].min.max(right_box[2].min));
+                let max = Point3::new(left_box[0].max.min(right_box[0].max), left_box[1].max.min(right_box[1].max), left_box[2].max.min(right_box[2].max));
+                // 两个包围盒不相交时结果是空的，给一个退化的盒子
+                Aabb::new_from_two_points(min, max.max(min))
+            }
+            CsgOperation::Difference => left_box,
+        };
+        Csg {
+            operation,
+            left,
+            right,
+            bbox,
+        }
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if !self.bbox.hit(r, ray_t) {
+            return None;
+        }
+        self.spans(r, ray_t.max)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|rec| ray_t.surrounds(rec.t))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    /// 两边的区间在 t_max 之前都是完整的，所以结果在 t_max 之前也是准确的
+    fn spans(&self, r: &Ray, t_max: f64) -> Vec<Span> {
+        // (t, 是否来自 left, 是否进入该物体, 交点)
+        let mut events: Vec<(f64, bool, bool, HitRecord)> = Vec::new();
+        for (is_left, object) in [(true, &self.left), (false, &self.right)] {
+            for span in object.spans(r, t_max) {
+                events.push((span.enter.t, is_left, true, span.enter));
+                events.push((span.exit.t, is_left, false, span.exit));
+            }
+        }
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut spans = Vec::new();
+        let (mut in_left, mut in_right, mut inside) = (false, false, false);
+        let mut enter: Option<HitRecord> = None;
+        for (_, is_left, entering, mut rec) in events {
+            if is_left {
+                in_left = entering;
+            } else {
+                in_right = entering;
+            }
+            if self.operation.contains(in_left, in_right) == inside {
+                continue;
+            }
+            inside = !inside;
+            // 减去的物体的表面朝向反过来：进入右边正是离开结果
+            rec.front_face = inside;
+            if inside {
+                if rec.t > t_max {
+                    break;
+                }
+                enter = Some(rec);
+            } else if let Some(enter) = enter.take() {
+                let beyond = rec.t > t_max;
+                spans.push(Span { enter, exit: rec });
+                if beyond {
+                    break;
+                }
+            }
+        }
+        spans
+    }
+}
@@ -1,3 +1,5 @@
+use crate::color::Color;
+
 pub enum Scene {
     BouncingSpheres,
     CheckeredSpheres,
@@ -8,14 +10,93 @@ pub enum Scene {
     SimpleLight,
     CornellBox,
     CornellSmoke,
+    Materials,
+    DeltaLights,
+    EnvironmentLighting,
+    PhysicalSky,
+    ProceduralTextures,
+    HeterogeneousMedia,
+    Primitives,
+    ConstructiveSolids,
+    DistanceFields,
+    Terrain,
+    Subdivision,
+    TextureSampling,
     FinalSceneLD,
     FinalSceneHD,
 }
 
+#[derive(Clone, Copy)]
 pub enum NoiseType {
     HashedRandom,
     TrilinearInterpolation,
     LatticeRandomVectors,
     Turbulence,
-    TurbulenceMarble,
-}
\ No newline at end of file
+    TurbulenceMarble, // sin(scale * z + 10 * turb(p))，scale 只决定条纹的疏密，扰动和原书一样不缩放
+    Simplex,
+    Worley(CellularFeature),
+    DomainWarped(f64), // 先用 fBm 扭曲输入坐标再求 fBm，参数是扭曲的强度
+}
+
+/// Worley 噪声输出的距离，F1、F2 分别是到最近、第二近特征点的距离，超过 1 的部分截断
+#[derive(Clone, Copy)]
+pub enum CellularFeature {
+    F1,
+    F2,
+    F2MinusF1, // 细胞边界处为 0，适合做石砖缝
+}
+
+/// 面光源的方向分布，角度都是角度制
+pub enum EmissionProfile {
+    Diffuse,                                       // 各方向辐射亮度相同
+    CosinePower(f64),                              // 辐射亮度按 cos^n 衰减，n 越大越集中在法线方向
+    Spot { cone_angle: f64, falloff_angle: f64 },  // falloff_angle 之内全亮，到 cone_angle 平滑衰减到 0
+}
+
+/// 参与介质的相函数，g 是不对称参数：g > 0 偏向前向散射，g < 0 偏向后向散射，g = 0 为各向同性
+#[derive(Clone, Copy)]
+pub enum PhaseFunction {
+    Isotropic,
+    HenyeyGreenstein(f64),
+    DoubleHenyeyGreenstein { forward: f64, backward: f64, weight: f64 }, // weight 是 forward 那一瓣所占的比例
+}
+
+/// CSG 的布尔运算，Difference 是左边减去右边
+#[derive(Clone, Copy)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+/// 控制网格的细分规则。Catmull–Clark 适用于任意多边形，细分一次后全是四边形；Loop 只适用于三角形，其他多边形会先拆成三角形
+#[derive(Clone, Copy)]
+pub enum SubdivisionScheme {
+    CatmullClark,
+    Loop,
+}
+
+/// 纹理坐标超出 [0, 1] 时的处理方式
+#[derive(Clone, Copy)]
+pub enum WrapMode {
+    Repeat,        // 平铺
+    Mirror,        // 镜像平铺
+    Clamp,         // 夹到边缘
+    Border(Color), // 超出范围时返回固定颜色，alpha 为 0，可以当贴花用
+}
+
+/// 图片纹理的过滤方式
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    Nearest,
+    Bilinear,
+    Bicubic,   // Catmull-Rom 样条，4 x 4 个像素
+    Trilinear, // 按光线锥的覆盖范围在相邻两层 mipmap 上分别双线性插值再混合
+}
+
+/// 图片纹理像素值的编码方式
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,   // 颜色贴图，读取时解码到线性空间
+    Linear, // 粗糙度、高度等数据贴图，按原值读取
+}
@@ -0,0 +1,163 @@
+use std::f64::consts::PI;
+
+use glam::DVec3;
+
+use crate::color::Color;
+use crate::random_number_generator::random;
+
+/// 等距柱状投影（equirectangular）的 HDR 环境贴图，支持 Radiance .hdr 和 OpenEXR。
+/// 按像素亮度 * sinθ 建立二维 CDF 做重要性采样，让户外场景的漫反射表面收敛更快
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    intensity: f64,
+    sin_rotation: f64,
+    cos_rotation: f64,
+    marginal_cdf: Vec<f64>,         // 按行的累积分布，长度 height + 1
+    conditional_cdf: Vec<Vec<f64>>, // 每行内按列的累积分布，长度 width + 1
+    row_weights: Vec<f64>,          // 每行权重之和，除以 total_weight 就是选中该行的概率
+    total_weight: f64,
+    visible_only: Option<Vec<Color>>, // 只有直接看到时才算的一层，不参与重要性采样
+}
+
+impl EnvironmentMap {
+    /// rotation 为绕 y 轴旋转的角度（角度制），intensity 为整体亮度缩放
+    pub fn new(filename: &str, rotation: f64, intensity: f64) -> anyhow::Result<EnvironmentMap> {
+        let img = image::open(filename)?.into_rgb32f();
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        let pixels = img
+            .pixels()
+            .map(|pixel| Color::new(pixel[0] as f64, pixel[1] as f64, pixel[2] as f64))
+            .collect();
+        Ok(EnvironmentMap::new_from_pixels(width, height, pixels, rotation, intensity))
+    }
+
+    pub fn new_from_pixels(width: usize, height: usize, pixels: Vec<Color>, rotation: f64, intensity: f64) -> EnvironmentMap {
+        assert_eq!(pixels.len(), width * height, "pixels.len() must equal width * height");
+        let radians = rotation.to_radians();
+
+        // 纯黑的区域也要保留一点概率，否则只靠光源采样时会漏掉这些方向
+        let average_luminance = pixels.iter().map(luminance).sum::<f64>() / pixels.len() as f64;
+        let floor = 0.01 * average_luminance + 1e-6;
+
+        let mut conditional_cdf = Vec::with_capacity(height);
+        let mut row_weights = Vec::with_capacity(height);
+        for j in 0..height {
+            let sin_theta = (PI * (j as f64 + 0.5) / height as f64).sin();
+            let mut cdf = Vec::with_capacity(width + 1);
+            cdf.push(0.0);
+            for i in 0..width {
+                let weight = (luminance(&pixels[j * width + i]) + floor) * sin_theta;
+                cdf.push(cdf[i] + weight);
+            }
+            row_weights.push(cdf[width]);
+            conditional_cdf.push(cdf);
+        }
+
+        let mut marginal_cdf = Vec::with_capacity(height + 1);
+        marginal_cdf.push(0.0);
+        for j in 0..height {
+            marginal_cdf.push(marginal_cdf[j] + row_weights[j]);
+        }
+        let total_weight = marginal_cdf[height];
+
+        EnvironmentMap {
+            width,
+            height,
+            pixels,
+            intensity,
+            sin_rotation: radians.sin(),
+            cos_rotation: radians.cos(),
+            marginal_cdf,
+            conditional_cdf,
+            row_weights,
+            total_weight,
+            visible_only: None,
+        }
+    }
+
+    /// 叠加一层只在光线直接看到时出现的亮度，比如和 DirectionalLight 配套的太阳圆盘。
+    /// 漫反射表面已经通过光源采样得到了它的贡献，所以这一层不参与重要性采样，sampled_value 也不包含它
+    pub fn with_visible_only(mut self, pixels: Vec<Color>) -> EnvironmentMap {
+        assert_eq!(pixels.len(), self.width * self.height, "pixels.len() must equal width * height");
+        self.visible_only = Some(pixels);
+        self
+    }
+
+    /// 沿 direction 方向看到的环境光
+    pub fn value(&self, direction: DVec3) -> Color {
+        let (i, j) = self.pixel_of(direction);
+        let index = j * self.width + i;
+        let visible_only = self.visible_only.as_ref().map_or(Color::ZERO, |pixels| pixels[index]);
+        self.intensity * (self.pixels[index] + visible_only)
+    }
+
+    /// 直接采样环境光时 direction 方向的亮度，和 sample 的分布对应，不含 with_visible_only 叠加的那一层
+    pub fn sampled_value(&self, direction: DVec3) -> Color {
+        let (i, j) = self.pixel_of(direction);
+        self.intensity * self.pixels[j * self.width + i]
+    }
+
+    /// 按亮度重要性采样一个方向（单位向量），同时返回该方向的立体角概率密度
+    pub fn sample(&self) -> (DVec3, f64) {
+        let j = upper_bound(&self.marginal_cdf, random() * self.total_weight);
+        let row = &self.conditional_cdf[j];
+        let i = upper_bound(row, random() * self.row_weights[j]);
+
+        let s = (i as f64 + random()) / self.width as f64;
+        let t = (j as f64 + random()) / self.height as f64;
+        let direction = self.direction_of(s, t);
+        (direction, self.pdf(direction))
+    }
+
+    /// sample 在 direction 方向上的立体角概率密度
+    pub fn pdf(&self, direction: DVec3) -> f64 {
+        let (i, j) = self.pixel_of(direction);
+        let sin_theta = (PI * (j as f64 + 0.5) / self.height as f64).sin();
+        let row = &self.conditional_cdf[j];
+        let pixel_probability = (row[i + 1] - row[i]) / self.total_weight;
+        // (s, t) 上的密度换算到立体角：dω = sinθ dθ dφ = 2π² sinθ ds dt
+        pixel_probability * (self.width * self.height) as f64 / (2.0 * PI * PI * sin_theta)
+    }
+
+    // 世界方向转到贴图坐标系后对应的像素；和 Sphere::get_sphere_uv 的约定一致，第 0 行是正上方
+    fn pixel_of(&self, direction: DVec3) -> (usize, usize) {
+        let d = direction.normalize();
+        // 和 RotateY 一样，先把方向反向旋转到贴图自身的坐标系
+        let local = DVec3::new(
+            self.cos_rotation * d.x - self.sin_rotation * d.z,
+            d.y,
+            self.sin_rotation * d.x + self.cos_rotation * d.z,
+        );
+        let theta = (-local.y).clamp(-1.0, 1.0).acos();
+        let phi = (-local.z).atan2(local.x) + PI;
+        let s = phi / (2.0 * PI);
+        let t = 1.0 - theta / PI;
+        let i = ((s * self.width as f64) as usize).min(self.width - 1);
+        let j = ((t * self.height as f64) as usize).min(self.height - 1);
+        (i, j)
+    }
+
+    // pixel_of 的逆变换，s, t ∈ [0, 1) 分别从左到右、从上到下
+    fn direction_of(&self, s: f64, t: f64) -> DVec3 {
+        let phi = 2.0 * PI * s;
+        let theta = PI * (1.0 - t);
+        let local = DVec3::new(-phi.cos() * theta.sin(), -theta.cos(), phi.sin() * theta.sin());
+        DVec3::new(
+            self.cos_rotation * local.x + self.sin_rotation * local.z,
+            local.y,
+            -self.sin_rotation * local.x + self.cos_rotation * local.z,
+        )
+    }
+}
+
+pub fn luminance(color: &Color) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+// cdf 单调不减且 cdf[0] = 0，返回满足 cdf[k] <= x < cdf[k + 1] 的 k
+fn upper_bound(cdf: &[f64], x: f64) -> usize {
+    let k = cdf.partition_point(|&c| c <= x);
+    k.clamp(1, cdf.len() - 1) - 1
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::constant;
+use crate::constant_medium::boundary_intervals;
+use crate::dvec3::DVec3Ext;
+use crate::enums::PhaseFunction;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::{Anisotropic, Material};
+use crate::point3::Point3;
+use crate::random_number_generator::random;
+use crate::ray::Ray;
+
+/// 规则网格上的密度场，x 变化最快，其次是 y、z
+pub struct DensityGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    data: Vec<f64>,
+}
+
+impl DensityGrid {
+    /// f 的参数是网格内的归一化坐标，每个分量都在 [0, 1]，取在体素中心
+    pub fn new_from_fn(nx: usize, ny: usize, nz: usize, f: impl Fn(DVec3) -> f64) -> DensityGrid {
+        let mut data = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let local = DVec3::new(
+                        (i as f64 + 0.5) / nx as f64,
+                        (j as f64 + 0.5) / ny as f64,
+                        (k as f64 + 0.5) / nz as f64,
+                    );
+                    data.push(f(local).max(0.0));
+                }
+            }
+        }
+        DensityGrid { nx, ny, nz, data }
+    }
+
+    /// 读取简单的二进制密度文件：开头是三个小端 u32 表示 nx、ny、nz，
+    /// 后面紧跟 nx * ny * nz 个小端 f32，排列顺序同上
+    pub fn new_from_file(filename: &str) -> anyhow::Result<DensityGrid> {
+        let bytes = fs::read(filename)?;
+        let read_u32 = |offset: usize| -> anyhow::Result<usize> {
+            let word = bytes
+                .get(offset..offset + 4)
+                .ok_or_else(|| anyhow::anyhow!("{} is too short for a density grid header", filename))?;
+            Ok(u32::from_le_bytes(word.try_into()?) as usize)
+        };
+        let (nx, ny, nz) = (read_u32(0)?, read_u32(4)?, read_u32(8)?);
+
+        // 头部不可信，维度相乘溢出时直接报错
+        let count = nx
+            .checked_mul(ny)
+            .and_then(|n| n.checked_mul(nz))
+            .filter(|&n| n.checked_mul(4).is_some())
+            .ok_or_else(|| anyhow::anyhow!("{} has an invalid density grid size {} x {} x {}", filename, nx, ny, nz))?;
+        let body = &bytes[12..];
+        if count == 0 || body.len() != count * 4 {
+            anyhow::bail!("{} should contain {} densities after the header, found {} bytes", filename, count, body.len());
+        }
+        // NaN、无穷大会让 majorant 失效，delta tracking 永远走不出去；负的密度当作 0
+        let mut data = Vec::with_capacity(count);
+        for (index, word) in body.chunks_exact(4).enumerate() {
+            let density = f32::from_le_bytes(word.try_into()?) as f64;
+            if !density.is_finite() {
+                anyhow::bail!("{} has a non-finite density {} at index {}", filename, density, index);
+            }
+            data.push(density.max(0.0));
+        }
+        Ok(DensityGrid { nx, ny, nz, data })
+    }
+
+    pub fn max_density(&self) -> f64 {
+        self.data.iter().copied().fold(0.0, f64::max)
+    }
+
+    fn voxel(&self, i: isize, j: isize, k: isize) -> f64 {
+        let i = i.clamp(0, self.nx as isize - 1) as usize;
+        let j = j.clamp(0, self.ny as isize - 1) as usize;
+        let k = k.clamp(0, self.nz as isize - 1) as usize;
+        self.data[(k * self.ny + j) * self.nx + i]
+    }
+
+    /// 三线性插值，local 在 [0, 1]^3 之外时密度为 0
+    pub fn lookup(&self, local: DVec3) -> f64 {
+        if local.min_element() < 0.0 || local.max_element() > 1.0 {
+            return 0.0;
+        }
+        // 体素中心位于 (i + 0.5) / n
+        let x = local.x * self.nx as f64 - 0.5;
+        let y = local.y * self.ny as f64 - 0.5;
+        let z = local.z * self.nz as f64 - 0.5;
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (u, v, w) = (x - x0, y - y0, z - z0);
+        let (i, j, k) = (x0 as isize, y0 as isize, z0 as isize);
+
+        let mut accum = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let weight = (if di == 1 { u } else { 1.0 - u })
+                        * (if dj == 1 { v } else { 1.0 - v })
+                        * (if dk == 1 { w } else { 1.0 - w });
+                    accum += weight * self.voxel(i + di, j + dj, k + dk);
+                }
+            }
+        }
+        accum
+    }
+}
+
+/// 密度由网格给出的非均匀介质，网格铺满 boundary 的包围盒。
+/// 用不小于任何位置密度的 majorant 做 delta tracking 采样散射点，阴影光线用 ratio tracking 估计透射率
+pub struct GridMedium {
+    boundary: Arc<dyn Hittable>,
+    grid: DensityGrid,
+    grid_min: Point3,
+    grid_size: DVec3,
+    density_scale: f64,
+    majorant: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl GridMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, grid: DensityGrid, density_scale: f64, albedo: Color, phase: PhaseFunction) -> GridMedium {
+        let bbox = boundary.bounding_box();
+        let grid_min = Point3::new(bbox[0].min, bbox[1].min, bbox[2].min);
+        let grid_size = DVec3::new(bbox[0].size(), bbox[1].size(), bbox[2].size());
+        let majorant = density_scale * grid.max_density();
+        GridMedium {
+            boundary,
+            grid,
+            grid_min,
+            grid_size,
+            density_scale,
+            majorant,
+            phase_function: Arc::new(Anisotropic::new_from_solid_color(albedo, phase)),
+        }
+    }
+
+    fn density(&self, p: Point3) -> f64 {
+        self.density_scale * self.grid.lookup((p - self.grid_min) / self.grid_size)
+    }
+
+    /// 按 majorant 的均匀介质走下一步，返回新的光线参数
+    fn step(&self, t: f64, ray_length: f64) -> f64 {
+        t - (1.0 - random()).ln() / (self.majorant * ray_length)
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.majorant <= 0.0 {
+            return None;
+        }
+        let ray_length = r.direction().length();
+
+        // delta tracking：按 density / majorant 的概率接受为真实碰撞，否则是虚碰撞，继续往前走。
+        // 指数分布没有记忆，每一段都可以从头开始
+        for inside in boundary_intervals(self.boundary.as_ref(), r, ray_t) {
+            let mut t = inside.min;
+            loop {
+                t = self.step(t, ray_length);
+                if t >= inside.max {
+                    break;
+                }
+                if random() * self.majorant < self.density(r.at(t)) {
+                    return Some(HitRecord::with_hit_data(
+                        t,
+                        r.at(t),
+                        (0.0, 0.0),
+                        r,
+                        DVec3::random_unit(), // 随机的法线
+                        self.phase_function.clone(),
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        if self.majorant <= 0.0 {
+            return Color::ONE;
+        }
+        let ray_length = r.direction().length();
+
+        // ratio tracking：每个虚碰撞点乘上该处不被吸收或散射的比例，透射率很小时用轮盘赌提前结束
+        let mut transmittance = 1.0;
+        for inside in boundary_intervals(self.boundary.as_ref(), r, ray_t) {
+            let mut t = inside.min;
+            loop {
+                t = self.step(t, ray_length);
+                if t >= inside.max {
+                    break;
+                }
+                transmittance *= 1.0 - self.density(r.at(t)) / self.majorant;
+                if transmittance < constant::TRANSMITTANCE_ROULETTE_THRESHOLD {
+                    if random() < 0.5 {
+                        return Color::ZERO;
+                    }
+                    transmittance *= 2.0;
+                }
+            }
+        }
+        Color::splat(transmittance)
+    }
+}
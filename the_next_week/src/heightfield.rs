@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::enums::ColorSpace;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::point3::Point3;
+use crate::ray::Ray;
+use crate::shape::intersect_triangle;
+use crate::texture::Texture;
+use crate::texture_cache::TextureCache;
+
+/// 规则网格上的高度场地形。网格点 (i, j) 位于 origin + (i * dx, height * size.y, j * dz)，
+/// 每个格子拆成两个三角形，法线在网格点上按相邻高度差估计后做插值，看不出三角形的棱。
+/// 求交时用格子高度最小值、最大值组成的四叉树（min-max mipmap）跳过光线碰不到的区域，不用把每个三角形都放进 BVH
+pub struct Heightfield {
+    nx: usize, // x 方向的网格点数
+    nz: usize, // z 方向的网格点数
+    heights: Vec<f64>, // 归一化到 [0, 1] 的高度，按行存放，heights[j * nx + i]
+    normals: Vec<DVec3>,
+    origin: Point3,
+    size: DVec3, // x、z 方向的范围和最大高度
+    cell_size: (f64, f64),
+    levels: Vec<MinMaxLevel>, // levels[0] 的每个元素对应一个格子，之后每层合并 2 x 2 个，直到只剩一个
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+/// 四叉树的一层，记录每个节点覆盖的所有格子的最低和最高高度
+struct MinMaxLevel {
+    width: usize,
+    depth: usize,
+    ranges: Vec<(f64, f64)>,
+}
+
+impl Heightfield {
+    /// 从灰度图读取高度，每个像素对应一个网格点，图片最上面一行在 z 最小的一侧。
+    /// 高度是数据而不是颜色，按线性空间读取，16 位图片可以避免地形出现台阶
+    pub fn new_from_image(filename: &str, origin: Point3, size: DVec3, mat: Arc<dyn Material>) -> anyhow::Result<Heightfield> {
+        let image = TextureCache::global().load(filename, ColorSpace::Linear)?;
+        let level = image.base();
+        if level.width < 2 || level.height < 2 {
+            anyhow::bail!("{} is too small for a heightfield: {} x {}", filename, level.width, level.height);
+        }
+        let heights = (0..level.height)
+            .flat_map(|j| (0..level.width).map(move |i| level.texel(i, j)))
+            .map(|texel| (texel.x + texel.y + texel.z) / 3.0)
+            .collect();
+        Ok(Heightfield::new(level.width, level.height, heights, origin, size, mat))
+    }
+
+    /// 在 nx x nz 个网格点上对纹理取样得到高度，比如 NoiseTexture。
+    /// 纹理坐标和命中点的 u、v 一致，p 是网格点在底面 y = origin.y 上的位置
+    pub fn new_from_texture(tex: &dyn Texture, nx: usize, nz: usize, origin: Point3, size: DVec3, mat: Arc<dyn Material>) -> Heightfield {
+        let (nx, nz) = (nx.max(2), nz.max(2));
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let (fx, fz) = (i as f64 / (nx - 1) as f64, j as f64 / (nz - 1) as f64);
+                let p = origin + DVec3::new(fx * size.x, 0.0, fz * size.z);
+                let color = tex.value(fx, 1.0 - fz, p);
+                heights.push((color.x + color.y + color.z) / 3.0);
+            }
+        }
+        Heightfield::new(nx, nz, heights, origin, size, mat)
+    }
+
+    fn new(nx: usize, nz: usize, heights: Vec<f64>, origin: Point3, size: DVec3, mat: Arc<dyn Material>) -> Heightfield {
+        let cell_size = (size.x / (nx - 1) as f64, size.z / (nz - 1) as f64);
+
+        // 网格点上的法线，边界上用单侧差分
+        let mut normals = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let (i0, i1) = (i.saturating_sub(1), (i + 1).min(nx - 1));
+                let (j0, j1) = (j.saturating_sub(1), (j + 1).min(nz - 1));
+                let slope_x = (heights[j * nx + i1] - heights[j * nx + i0]) * size.y / ((i1 - i0) as f64 * cell_size.0);
+                let slope_z = (heights[j1 * nx + i] - heights[j0 * nx + i]) * size.y / ((j1 - j0) as f64 * cell_size.1);
+                normals.push(DVec3::new(-slope_x, 1.0, -slope_z).normalize());
+            }
+        }
+
+        let mut base = MinMaxLevel {
+            width: nx - 1,
+            depth: nz - 1,
+            ranges: Vec::with_capacity((nx - 1) * (nz - 1)),
+        };
+        for j in 0..nz - 1 {
+            for i in 0..nx - 1 {
+                let corners = [heights[j * nx + i], heights[j * nx + i + 1], heights[(j + 1) * nx + i], heights[(j + 1) * nx + i + 1]];
+                let min = corners.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                base.ranges.push((min, max));
+            }
+        }
+        let mut levels = vec![base];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.depth > 1) {
+            let next = last.merge();
+            levels.push(next);
+        }
+
+        let (min, max) = levels.last().unwrap().ranges[0];
+        let bbox = Aabb::new_from_two_points(
+            origin + DVec3::new(0.0, min * size.y, 0.0),
+            origin + DVec3::new(size.x, max * size.y, size.z),
+        );
+
+        Heightfield {
+            nx,
+            nz,
+            heights,
+            normals,
+            origin,
+            size,
+            cell_size,
+            levels,
+            mat,
+            bbox,
+        }
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> Point3 {
+        self.origin + DVec3::new(i as f64 * self.cell_size.0, self.heights[j * self.nx + i] * self.size.y, j as f64 * self.cell_size.1)
+    }
+
+    /// 四叉树第 level 层的节点 (i, j) 的包围盒
+    fn node_bounding_box(&self, level: usize, i: usize, j: usize) -> Aabb {
+        let cells = 1 << level;
+        let base = &self.levels[0];
+        let (i0, i1) = (i * cells, ((i + 1) * cells).min(base.width));
+        let (j0, j1) = (j * cells, ((j + 1) * cells).min(base.depth));
+        let (min, max) = self.levels[level].range(i, j);
+        Aabb::new_from_two_points(
+            self.origin + DVec3::new(i0 as f64 * self.cell_size.0, min * self.size.y, j0 as f64 * self.cell_size.1),
+            self.origin + DVec3::new(i1 as f64 * self.cell_size.0, max * self.size.y, j1 as f64 * self.cell_size.1),
+        )
+    }
+
+    /// 在 closest 之内找更近的交点，找到就更新 closest
+    fn hit_node(&self, r: &Ray, ray_t: Interval, level: usize, i: usize, j: usize, closest: &mut Option<HitRecord>) {
+        let t_max = closest.as_ref().map_or(ray_t.max, |rec| rec.t);
+        if self.node_bounding_box(level, i, j).hit_interval(r, Interval::new(ray_t.min, t_max)).is_none() {
+            return;
+        }
+        if level == 0 {
+            for triangle in [[(i, j), (i + 1, j), (i + 1, j + 1)], [(i, j), (i + 1, j + 1), (i, j + 1)]] {
+                let t_max = closest.as_ref().map_or(ray_t.max, |rec| rec.t);
+                if let Some(rec) = self.hit_triangle(r, Interval::new(ray_t.min, t_max), triangle) {
+                    *closest = Some(rec);
+                }
+            }
+            return;
+        }
+        // 按光线方向从近到远访问子节点，先找到的交点会让后面的节点在包围盒测试时就被排除
+        let child_level = &self.levels[level - 1];
+        let (fi, fj) = (usize::from(r.direction().x < 0.0), usize::from(r.direction().z < 0.0));
+        for (di, dj) in [(fi, fj), (1 - fi, fj), (fi, 1 - fj), (1 - fi, 1 - fj)] {
+            let (ci, cj) = (2 * i + di, 2 * j + dj);
+            if ci < child_level.width && cj < child_level.depth {
+                self.hit_node(r, ray_t, level - 1, ci, cj, closest);
+            }
+        }
+    }
+
+    /// 顶点按网格坐标给出，法线和纹理坐标用重心坐标插值
+    fn hit_triangle(&self, r: &Ray, ray_t: Interval, triangle: [(usize, usize); 3]) -> Option<HitRecord> {
+        let (t, beta, gamma) = intersect_triangle(r, ray_t, triangle.map(|(i, j)| self.vertex(i, j)))?;
+
+        let weights = [1.0 - beta - gamma, beta, gamma];
+        let mut normal = DVec3::ZERO;
+        let (mut u, mut v) = (0.0, 0.0);
+        for (&(i, j), weight) in triangle.iter().zip(weights) {
+            normal += weight * self.normals[j * self.nx + i];
+            u += weight * i as f64 / (self.nx - 1) as f64;
+            v += weight * (1.0 - j as f64 / (self.nz - 1) as f64);
+        }
+
+        // 切线忽略了高度的变化，tangent_frame 会把它投影到切平面上
+        let dpdu = DVec3::new(self.size.x, 0.0, 0.0);
+        let dpdv = DVec3::new(0.0, 0.0, -self.size.z);
+        let rec = HitRecord::with_hit_data(t, r.at(t), (u, v), r, normal.normalize(), self.mat.clone()).with_tangents(dpdu, dpdv);
+        Some(rec)
+    }
+}
+
+impl MinMaxLevel {
+    fn range(&self, i: usize, j: usize) -> (f64, f64) {
+        self.ranges[j * self.width + i]
+    }
+
+    /// 每 2 x 2 个节点合并成上一层的一个节点，奇数边长时最后一行/列单独成为一个节点
+    fn merge(&self) -> MinMaxLevel {
+        let width = self.width.div_ceil(2);
+        let depth = self.depth.div_ceil(2);
+        let mut ranges = Vec::with_capacity(width * depth);
+        for j in 0..depth {
+            for i in 0..width {
+                let mut merged = (f64::INFINITY, f64::NEG_INFINITY);
+                for (di, dj) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (ci, cj) = (2 * i + di, 2 * j + dj);
+                    if ci < self.width && cj < self.depth {
+                        let (min, max) = self.range(ci, cj);
+                        merged = (merged.0.min(min), merged.1.max(max));
+                    }
+                }
+                ranges.push(merged);
+            }
+        }
+        MinMaxLevel { width, depth, ranges }
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let mut closest = None;
+        self.hit_node(r, ray_t, self.levels.len() - 1, 0, 0, &mut closest);
+        closest
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
@@ -2,11 +2,13 @@ use std::sync::Arc;
 
 use glam::DVec3;
 
+use crate::constant;
 use crate::material::Material;
 use crate::point3::Point3;
 use crate::ray::Ray;
 
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3,
     pub unit_normal: DVec3, // Sphere hit 时，会计算 normal，用 (p - center) / radius，已经被单位化了
@@ -15,6 +17,9 @@ pub struct HitRecord {
     pub u: f64, // the u,v surface coordinates of the ray-object hit point. 纹理坐标，用于纹理映射。
     pub v: f64,
     pub front_face: bool,
+    pub dpdu: DVec3, // 交点处 p 对纹理坐标 u, v 的偏导，构成切线空间。不支持的物体为零向量
+    pub dpdv: DVec3,
+    pub footprint: f64, // 相机光线锥在交点处覆盖的世界空间宽度，用于选择纹理的 mipmap 层级，0 表示未知
 }
 
 impl HitRecord {
@@ -41,6 +46,41 @@ impl HitRecord {
             u,
             v,
             front_face,
+            dpdu: DVec3::ZERO,
+            dpdv: DVec3::ZERO,
+            footprint: 0.0,
+        }
+    }
+
+    pub fn with_tangents(mut self, dpdu: DVec3, dpdv: DVec3) -> HitRecord {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// footprint 换算到纹理坐标上的宽度，取 u、v 两个方向中较大的一个
+    pub fn uv_footprint(&self) -> f64 {
+        let (dpdu_length, dpdv_length) = (self.dpdu.length(), self.dpdv.length());
+        if self.footprint <= 0.0 || dpdu_length < constant::NEAR_ZERO_THRESHOLD || dpdv_length < constant::NEAR_ZERO_THRESHOLD {
+            return 0.0;
+        }
+        (self.footprint / dpdu_length).max(self.footprint / dpdv_length)
+    }
+
+    /// 以 unit_normal 为 z 轴的正交切线空间 (tangent, bitangent)，bitangent 和 dpdv 同向。
+    /// 没有切线信息时任取一组垂直于法线的基
+    pub fn tangent_frame(&self) -> (DVec3, DVec3) {
+        let n = self.unit_normal;
+        let tangent = self.dpdu - n * n.dot(self.dpdu);
+        if tangent.length_squared() < constant::NEAR_ZERO_THRESHOLD {
+            return n.any_orthonormal_pair();
+        }
+        let tangent = tangent.normalize();
+        let bitangent = n.cross(tangent);
+        if bitangent.dot(self.dpdv) < 0.0 {
+            (tangent, -bitangent)
+        } else {
+            (tangent, bitangent)
         }
     }
 
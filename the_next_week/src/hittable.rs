@@ -1,10 +1,59 @@
-
 use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::constant;
 use crate::hit_record::HitRecord;
 use crate::interval::Interval;
 use crate::ray::Ray;
 
+/// 光线在封闭物体内部的一段，enter、exit 分别是进入和离开时的交点
+pub struct Span {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
+}
+
 pub trait Hittable: Send + Sync {
     fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord>;
     fn bounding_box(&self) -> Aabb;
-}
\ No newline at end of file
+
+    /// 光线在 ray_t 范围内穿过这个物体后剩下的比例，用于阴影光线。
+    /// 默认依次穿过每个交点，乘上材质透过的比例，碰到不透明的地方就是 0；参与介质可以给出解析值或者无偏估计
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        let mut transmittance = 1.0;
+        let mut cursor = ray_t.min;
+        while let Some(rec) = self.hit(r, Interval::new(cursor, ray_t.max)) {
+            transmittance *= 1.0 - rec.mat.opacity(&rec);
+            if transmittance <= 0.0 {
+                return Color::ZERO;
+            }
+            cursor = rec.t + constant::RAY_MIN_DISTANCE;
+        }
+        Color::splat(transmittance)
+    }
+
+    /// 整条直线（t 从 -∞ 开始）在物体内部的区间，按 t 从小到大排列，用于 CSG 和介质的边界。
+    /// 默认从 t = -∞ 开始依次找出每个交点，每穿过一次就在内外之间切换一次，
+    /// 所以凹形物体、光线起点在物体里面都能正确处理，只要求物体是封闭的。
+    /// 只需要 t_max 之前的部分：越过 t_max 的第一个交点之后就不再求交，
+    /// 最后一个区间的终点可能在 t_max 之后，调用方自己截断
+    fn spans(&self, r: &Ray, t_max: f64) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut enter: Option<HitRecord> = None;
+        let mut cursor = f64::NEG_INFINITY;
+        while let Some(rec) = self.hit(r, Interval::new(cursor, f64::INFINITY)) {
+            // 跳过同一位置重复的交点，比如光线恰好穿过两个面的公共棱
+            cursor = rec.t + constant::RAY_MIN_DISTANCE;
+            let beyond = rec.t > t_max;
+            match enter.take() {
+                Some(enter) => spans.push(Span { enter, exit: rec }),
+                None if beyond => break,
+                None => enter = Some(rec),
+            }
+            if beyond {
+                break;
+            }
+        }
+        // 封闭的物体不会只进不出。走完仍在内部，说明光线擦过棱或者切过曲面时两个交点被当成了一个，
+        // 这一段不算，否则会得到无穷长的区间
+        spans
+    }
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::aabb::Aabb;
+use crate::color::Color;
 use crate::hit_record::HitRecord;
 use crate::hittable::Hittable;
 use crate::interval::Interval;
@@ -44,4 +45,15 @@ impl Hittable for HittableList {
     fn bounding_box(&self) -> Aabb {
         self.bbox
     }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        let mut transmittance = Color::ONE;
+        for object in &self.objects {
+            transmittance *= object.transmittance(r, ray_t);
+            if transmittance == Color::ZERO {
+                break;
+            }
+        }
+        transmittance
+    }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use glam::DVec3;
+
+use crate::color::Color;
+use crate::enums::EmissionProfile;
+use crate::point3::Point3;
+
+/// 从交点看向光源的一次采样
+pub struct LightSample {
+    pub direction: DVec3, // 指向光源的单位向量
+    pub distance: f64,    // 到光源的距离，阴影光线只检查这一段，平行光为无穷远
+    pub radiance: Color,  // 到达交点的光照，已经包含距离衰减
+}
+
+/// 点光源、聚光灯、平行光这类 delta 光源，随机弹射永远打不中，
+/// 需要在每个非镜面的交点上发阴影光线去直接采样
+pub trait Light: Send + Sync {
+    fn sample(&self, p: Point3) -> Option<LightSample>;
+}
+
+pub struct PointLight {
+    position: Point3,
+    intensity: Color, // 发光强度，单位立体角的功率
+}
+
+impl PointLight {
+    pub fn new(position: Point3, intensity: Color) -> PointLight {
+        PointLight { position, intensity }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let distance_squared = to_light.length_squared();
+        let distance = distance_squared.sqrt();
+        Some(LightSample {
+            direction: to_light / distance,
+            distance,
+            radiance: self.intensity / distance_squared,
+        })
+    }
+}
+
+pub struct SpotLight {
+    position: Point3,
+    unit_direction: DVec3,
+    intensity: Color,
+    profile: EmissionProfile,
+}
+
+impl SpotLight {
+    /// cone_angle、falloff_angle 为角度制，falloff_angle 之内全亮，到 cone_angle 平滑衰减到 0
+    pub fn new(position: Point3, target: Point3, intensity: Color, cone_angle: f64, falloff_angle: f64) -> SpotLight {
+        SpotLight {
+            position,
+            unit_direction: (target - position).normalize(),
+            intensity,
+            profile: EmissionProfile::Spot { cone_angle, falloff_angle },
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, p: Point3) -> Option<LightSample> {
+        let to_light = self.position - p;
+        let distance_squared = to_light.length_squared();
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let falloff = self.profile.falloff(self.unit_direction.dot(-direction));
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: falloff * self.intensity / distance_squared,
+        })
+    }
+}
+
+/// 平行光（太阳光），所有位置接收到的光照相同
+pub struct DirectionalLight {
+    to_light: DVec3,
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    /// direction 是光线前进的方向，例如 (0, -1, 0) 表示正上方照下来
+    pub fn new(direction: DVec3, irradiance: Color) -> DirectionalLight {
+        DirectionalLight {
+            to_light: -direction.normalize(),
+            irradiance,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _p: Point3) -> Option<LightSample> {
+        Some(LightSample {
+            direction: self.to_light,
+            distance: f64::INFINITY,
+            radiance: self.irradiance,
+        })
+    }
+}
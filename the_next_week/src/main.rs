@@ -1,4 +1,5 @@
 mod aabb;
+mod atmosphere;
 mod bvh;
 mod camera;
 mod color;
@@ -6,40 +7,64 @@ mod config;
 
 mod constant;
 mod constant_medium;
+mod csg;
 mod dvec3;
 mod enums;
+mod environment;
+mod grid_medium;
+mod heightfield;
 mod hit_record;
 mod hittable;
 mod hittable_list;
 mod interval;
+mod light;
 mod material;
 mod perlin;
 mod point3;
+mod primitive;
 mod shape;
+mod sky;
 mod transform;
 mod ray;
 mod random_number_generator;
+mod sdf;
 mod sphere;
+mod subdivision;
+mod subsurface;
 mod texture;
+mod texture_cache;
 
 use std::sync::Arc;
 
+use aabb::Aabb;
+use atmosphere::Atmosphere;
 use bvh::BvhNode;
 use camera::Camera;
 use color::Color;
 use constant_medium::ConstantMedium;
+use csg::Csg;
 use dvec3::DVec3Ext;
-use enums::Scene;
-use glam::DVec3;
+use enums::{CellularFeature, ColorSpace, CsgOperation, EmissionProfile, FilterMode, NoiseType, PhaseFunction, Scene, SubdivisionScheme, WrapMode};
+use environment::EnvironmentMap;
+use glam::{DVec2, DVec3};
+use grid_medium::{DensityGrid, GridMedium};
+use heightfield::Heightfield;
 use hittable::Hittable;
 use hittable_list::HittableList;
-use material::{Dielectric, DiffuseLight, Lambertian, Metal};
+use light::{DirectionalLight, PointLight, SpotLight};
+use material::{AlphaCutout, BumpMapped, Coated, Dielectric, DiffuseLight, DiffuseTransmission, Lambertian, Metal, MixMaterial, NormalMapped, OrenNayar};
+use perlin::Perlin;
 use point3::Point3;
-use shape::{Annulus, Ellipse, Quad, Tri};
+use primitive::{Capsule, Cone, Cylinder, Torus};
+use shape::{Annulus, BilinearPatch, Ellipse, Plane, Quad, Tri};
+use sky::PhysicalSky;
 use random_number_generator::{random, random_range};
 
+use sdf::Sdf;
 use sphere::Sphere;
-use texture::{CheckerTexture, ImageTexture, NoiseTexture};
+use subdivision::ControlMesh;
+use subsurface::Subsurface;
+use texture::{AlphaChannel, CheckerTexture, ColorRamp, ImageTexture, NoiseTexture, Texture, UvCheckerTexture, UvTransform};
 use transform::{RotateY, Translate};
 
 fn bouncing_spheres() -> anyhow::Result<()> {
@@ -196,7 +221,7 @@ fn checkered_spheres() -> anyhow::Result<()> {
 
 fn earth() -> anyhow::Result<()> {
     let mut world = HittableList::new();
-    let earth_texture = Arc::new(ImageTexture::new("earthmap.jpg")?);
+    let earth_texture = Arc::new(ImageTexture::new_with_options("earthmap.jpg", WrapMode::Repeat, FilterMode::Trilinear, ColorSpace::Srgb)?);
     let earth_surface = Arc::new(Lambertian::new_from_texture(earth_texture));
     let globe = Arc::new(Sphere::new_static(Point3::ZERO, 2.0, earth_surface));
     world.add(globe);
@@ -222,7 +247,11 @@ fn earth() -> anyhow::Result<()> {
 
 fn perlin_spheres() -> anyhow::Result<()> {
     let mut world = HittableList::new();
-    let texture = Arc::new(NoiseTexture::new(config::config_perlin_spheres::INPUT_POINT_SCALE));
+    let texture = Arc::new(NoiseTexture::new(
+        config::config_perlin_spheres::NOISE_TYPE,
+        config::config_perlin_spheres::INPUT_POINT_SCALE,
+        config::config_perlin_spheres::NOISE_SEED,
+    ));
 
     world.add(Arc::new(Sphere::new_static(
         Point3::new(0.0, -1000.0, 0.0),
@@ -329,7 +358,11 @@ fn shapes() -> anyhow::Result<()> {
 fn simple_light() -> anyhow::Result<()> {
     let mut world = HittableList::new();
     
-    let perlin_texture = Arc::new(NoiseTexture::new(config::config_simple_light::INPUT_POINT_SCALE));
+    let perlin_texture = Arc::new(NoiseTexture::new(
+        config::config_simple_light::NOISE_TYPE,
+        config::config_simple_light::INPUT_POINT_SCALE,
+        config::config_simple_light::NOISE_SEED,
+    ));
     world.add(Arc::new(Sphere::new_static(
         Point3::new(0.0, -1000.0, 0.0),
         1000.0,
@@ -441,6 +474,13 @@ fn cornell_smoke() -> anyhow::Result<()> {
     cam.max_depth = config::config_cornell_smoke::MAX_DEPTH;
     cam.background = config::config_cornell_smoke::BACKGROUND;
     cam.enable_gradient_sky = config::config_cornell_smoke::ENABLE_GRADIENT_SKY;
+    cam.medium = config::config_cornell_smoke::AMBIENT_FOG_DENSITY.map(|density| -> Arc<dyn Hittable> {
+        Arc::new(ConstantMedium::new_unbounded(
+            density,
+            config::config_cornell_smoke::AMBIENT_FOG_ALBEDO,
+            PhaseFunction::Isotropic,
+        ))
+    });
 
     cam.vfov = config::config_cornell_smoke::V_FOV;
     cam.lookfrom = config::config_cornell_smoke::LOOKFROM;
@@ -453,6 +493,1029 @@ fn cornell_smoke() -> anyhow::Result<()> {
     cam.render(&world)    
 }
 
+fn materials() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let checker = Arc::new(CheckerTexture::new(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(checker)),
+    )));
+
+    // 同色的 Lambertian 和 Oren–Nayar 对比
+    let clay = Color::new(0.8, 0.45, 0.3);
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(-2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_solid_color(clay)),
+    )));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(OrenNayar::new_from_solid_color(clay, config::config_materials::ROUGHNESS_SIGMA)),
+    )));
+
+    // 纸灯笼：灯罩里面放一个小光源
+    let paper = Arc::new(DiffuseTransmission::new_from_solid_color(
+        Color::new(0.95, 0.85, 0.6),
+        config::config_materials::PAPER_TRANSMITTANCE,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(2.2, 1.0, 0.0), 1.0, paper)));
+    let bulb = Arc::new(DiffuseLight::new_from_solid_color(Color::new(20.0, 18.0, 14.0)));
+    world.add(Arc::new(Sphere::new_static(Point3::new(2.2, 1.0, 0.0), 0.3, bulb)));
+
+    // 最左边的蜡球，次表面散射。球自己的材质会被 Subsurface 替换，只是占位
+    let wax_boundary = Arc::new(Sphere::new_static(
+        Point3::new(-4.4, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_solid_color(Color::ZERO)),
+    ));
+    world.add(Arc::new(Subsurface::new_from_mean_free_path(
+        wax_boundary,
+        config::config_materials::WAX_MEAN_FREE_PATH,
+        config::config_materials::WAX_ALBEDO,
+        config::config_materials::WAX_REFRACTION_INDEX,
+    )));
+
+    // 前排：车漆和上了清漆的棋盘格
+    let car_paint = Arc::new(Coated::new(
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.6, 0.02, 0.02))),
+        config::config_materials::COAT_REFRACTION_INDEX,
+        0.0,
+        0.0,
+        Color::ZERO,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(-1.1, 0.5, 2.0), 0.5, car_paint)));
+    let wood = Arc::new(CheckerTexture::new(
+        0.1,
+        Color::new(0.45, 0.25, 0.1),
+        Color::new(0.3, 0.15, 0.05),
+    ));
+    let varnish = Arc::new(Coated::new(
+        Arc::new(Lambertian::new_from_texture(wood)),
+        config::config_materials::COAT_REFRACTION_INDEX,
+        config::config_materials::VARNISH_ROUGHNESS,
+        config::config_materials::VARNISH_THICKNESS,
+        config::config_materials::VARNISH_ABSORPTION,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(1.1, 0.5, 2.0), 0.5, varnish)));
+
+    // 噪声纹理控制金属和漫反射的混合
+    let marble_mask = Arc::new(NoiseTexture::new(
+        NoiseType::TurbulenceMarble,
+        config::config_materials::MIX_MASK_SCALE,
+        config::config_materials::MIX_MASK_SEED,
+    ));
+    let marble = Arc::new(MixMaterial::new(
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.05)),
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.1, 0.1, 0.12))),
+        marble_mask,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(0.0, 0.5, 2.5), 0.5, marble)));
+
+    // 两侧：噪声凹凸贴图和棋盘格编码的法线贴图
+    let bumps = Arc::new(NoiseTexture::new(
+        NoiseType::Turbulence,
+        config::config_materials::BUMP_NOISE_SCALE,
+        config::config_materials::BUMP_NOISE_SEED,
+    ));
+    let granite = Arc::new(
+        NoiseTexture::new(
+            NoiseType::LatticeRandomVectors,
+            config::config_materials::GRANITE_SCALE,
+            config::config_materials::GRANITE_SEED,
+        )
+        .with_octaves(4, 2.0, 0.6)
+        .with_color_ramp(ColorRamp::new(vec![
+            (0.3, Color::new(0.25, 0.2, 0.2)),
+            (0.5, Color::new(0.7, 0.6, 0.55)),
+            (0.7, Color::new(0.9, 0.88, 0.85)),
+        ])),
+    );
+    let bumped = Arc::new(BumpMapped::new(
+        Arc::new(Lambertian::new_from_texture(granite)),
+        bumps,
+        config::config_materials::BUMP_STRENGTH,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(-2.4, 0.5, 2.5), 0.5, bumped)));
+    let facets = Arc::new(CheckerTexture::new(
+        0.15,
+        Color::new(0.75, 0.5, 0.9),
+        Color::new(0.25, 0.5, 0.9),
+    ));
+    let faceted = Arc::new(NormalMapped::new(
+        Arc::new(Metal::new(Color::new(0.9, 0.75, 0.4), 0.1)),
+        facets,
+        1.0,
+    ));
+    world.add(Arc::new(Sphere::new_static(Point3::new(2.4, 0.5, 2.5), 0.5, faceted)));
+
+    // 背后的镂空栅栏
+    let fence_pattern: Arc<dyn Texture> = match config::config_materials::FENCE_IMAGE {
+        Some(filename) => Arc::new(AlphaChannel::new(Arc::new(ImageTexture::new_with_options(
+            filename,
+            WrapMode::Repeat,
+            FilterMode::Bilinear,
+            ColorSpace::Linear,
+        )?))),
+        None => Arc::new(UvCheckerTexture::new(2.0, 2.0, Color::ONE, Color::ZERO)),
+    };
+    let fence_mask = Arc::new(UvTransform::new(
+        fence_pattern,
+        config::config_materials::FENCE_TILING,
+        DVec2::ZERO,
+        config::config_materials::FENCE_ROTATION,
+        WrapMode::Repeat,
+    ));
+    let fence = Arc::new(AlphaCutout::new(
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.6, 0.6, 0.6))),
+        fence_mask,
+    ));
+    world.add(Arc::new(Quad::new(Point3::new(-4.0, 0.0, -2.0), DVec3::new(8.0, 0.0, 0.0), DVec3::new(0.0, 3.0, 0.0), fence)));
+
+    // 只朝下发光、略带方向性的顶灯，亮度用功率指定
+    let (light_u, light_v) = (DVec3::new(6.0, 0.0, 0.0), DVec3::new(0.0, 0.0, 3.0));
+    let light = Arc::new(DiffuseLight::new_from_power(
+        Color::ONE,
+        config::config_materials::CEILING_LIGHT_POWER,
+        light_u.cross(light_v).length(),
+        false,
+        EmissionProfile::CosinePower(config::config_materials::CEILING_LIGHT_EXPONENT),
+    ));
+    world.add(Arc::new(Quad::new(Point3::new(-3.0, 6.0, -1.0), light_u, light_v, light)));
+
+    // 照向右侧法线贴图球的聚光灯
+    let (spot_u, spot_v) = (DVec3::new(0.4, 0.0, 0.0), DVec3::new(0.0, 0.0, 0.4));
+    let spot = Arc::new(DiffuseLight::new_from_power(
+        Color::new(1.0, 0.9, 0.7),
+        config::config_materials::SPOT_LIGHT_POWER,
+        spot_u.cross(spot_v).length(),
+        false,
+        EmissionProfile::Spot {
+            cone_angle: config::config_materials::SPOT_CONE_ANGLE,
+            falloff_angle: config::config_materials::SPOT_FALLOFF_ANGLE,
+        },
+    ));
+    world.add(Arc::new(Quad::new(Point3::new(2.2, 4.0, 2.3), spot_u, spot_v, spot)));
+
+    if config::ENABLE_BVH {
+        let bvh_node = BvhNode::new(world);
+        world = HittableList::new();
+        world.add(Arc::new(bvh_node));
+    }
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_materials::ASPECT_RATIO;
+    cam.image_width = config::config_materials::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_materials::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_materials::MAX_DEPTH;
+    cam.background = config::config_materials::BACKGROUND;
+    cam.enable_gradient_sky = config::config_materials::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_materials::V_FOV;
+    cam.lookfrom = config::config_materials::LOOKFROM;
+    cam.lookat = config::config_materials::LOOKAT;
+    cam.vup = config::config_materials::V_UP;
+
+    cam.defocus_angle = config::config_materials::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_materials::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn delta_lights() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_solid_color(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new_static(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(-2.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.3, 0.3))),
+    )));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.8), 0.0)),
+    )));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(2.0, 1.0, 0.0),
+        1.0,
+        Arc::new(OrenNayar::new_from_solid_color(Color::new(0.9, 0.9, 0.9), 20.0)),
+    )));
+
+    if config::ENABLE_BVH {
+        let bvh_node = BvhNode::new(world);
+        world = HittableList::new();
+        world.add(Arc::new(bvh_node));
+    }
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_delta_lights::ASPECT_RATIO;
+    cam.image_width = config::config_delta_lights::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_delta_lights::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_delta_lights::MAX_DEPTH;
+    cam.background = config::config_delta_lights::BACKGROUND;
+    cam.enable_gradient_sky = config::config_delta_lights::ENABLE_GRADIENT_SKY;
+    cam.lights = vec![
+        Arc::new(PointLight::new(
+            config::config_delta_lights::POINT_LIGHT_POSITION,
+            config::config_delta_lights::POINT_LIGHT_INTENSITY,
+        )),
+        Arc::new(SpotLight::new(
+            config::config_delta_lights::SPOT_LIGHT_POSITION,
+            config::config_delta_lights::SPOT_LIGHT_TARGET,
+            config::config_delta_lights::SPOT_LIGHT_INTENSITY,
+            config::config_delta_lights::SPOT_CONE_ANGLE,
+            config::config_delta_lights::SPOT_FALLOFF_ANGLE,
+        )),
+        Arc::new(DirectionalLight::new(
+            config::config_delta_lights::SUN_DIRECTION,
+            config::config_delta_lights::SUN_IRRADIANCE,
+        )),
+    ];
+
+    cam.vfov = config::config_delta_lights::V_FOV;
+    cam.lookfrom = config::config_delta_lights::LOOKFROM;
+    cam.lookat = config::config_delta_lights::LOOKAT;
+    cam.vup = config::config_delta_lights::V_UP;
+
+    cam.defocus_angle = config::config_delta_lights::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_delta_lights::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn environment_lighting() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_solid_color(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new_static(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(-2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.8, 0.8))),
+    )));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0)),
+    )));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(2.2, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    if config::ENABLE_BVH {
+        let bvh_node = BvhNode::new(world);
+        world = HittableList::new();
+        world.add(Arc::new(bvh_node));
+    }
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_environment::ASPECT_RATIO;
+    cam.image_width = config::config_environment::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_environment::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_environment::MAX_DEPTH;
+    cam.background = config::config_environment::BACKGROUND;
+    cam.enable_gradient_sky = config::config_environment::ENABLE_GRADIENT_SKY;
+    cam.environment = Some(Arc::new(EnvironmentMap::new(
+        config::config_environment::ENVIRONMENT_MAP,
+        config::config_environment::ENVIRONMENT_ROTATION,
+        config::config_environment::ENVIRONMENT_INTENSITY,
+    )?));
+
+    cam.vfov = config::config_environment::V_FOV;
+    cam.lookfrom = config::config_environment::LOOKFROM;
+    cam.lookat = config::config_environment::LOOKAT;
+    cam.vup = config::config_environment::V_UP;
+
+    cam.defocus_angle = config::config_environment::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_environment::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn physical_sky() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_solid_color(config::config_physical_sky::GROUND_ALBEDO));
+    world.add(Arc::new(Sphere::new_static(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    let white = Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.8, 0.8)));
+    world.add(Arc::new(Sphere::new_static(Point3::new(-2.2, 1.0, 0.0), 1.0, white.clone())));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Color::new(0.9, 0.9, 0.9), 0.0)),
+    )));
+    let mut tower: Arc<dyn Hittable> = Arc::new(Quad::cuboid(Point3::new(-0.6, 0.0, -0.6), Point3::new(0.6, 3.0, 0.6), white));
+    tower = Arc::new(RotateY::new(tower, 30.0));
+    tower = Arc::new(Translate::new(tower, DVec3::new(2.4, 0.0, -1.0)));
+    world.add(tower);
+
+    if config::ENABLE_BVH {
+        let bvh_node = BvhNode::new(world);
+        world = HittableList::new();
+        world.add(Arc::new(bvh_node));
+    }
+
+    let sky = PhysicalSky::new(
+        config::config_physical_sky::SUN_ELEVATION,
+        config::config_physical_sky::SUN_AZIMUTH,
+        config::config_physical_sky::TURBIDITY,
+        config::config_physical_sky::GROUND_ALBEDO,
+    )
+    .with_sun_angular_diameter(config::config_physical_sky::SUN_ANGULAR_DIAMETER);
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_physical_sky::ASPECT_RATIO;
+    cam.image_width = config::config_physical_sky::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_physical_sky::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_physical_sky::MAX_DEPTH;
+    cam.background = config::config_physical_sky::BACKGROUND;
+    cam.enable_gradient_sky = config::config_physical_sky::ENABLE_GRADIENT_SKY;
+    cam.environment = Some(Arc::new(sky.to_environment_map(
+        config::config_physical_sky::SKY_MAP_WIDTH,
+        config::config_physical_sky::SKY_MAP_HEIGHT,
+    )));
+    cam.lights = vec![Arc::new(sky.sun_light())];
+
+    cam.vfov = config::config_physical_sky::V_FOV;
+    cam.lookfrom = config::config_physical_sky::LOOKFROM;
+    cam.lookat = config::config_physical_sky::LOOKAT;
+    cam.vup = config::config_physical_sky::V_UP;
+
+    cam.defocus_angle = config::config_physical_sky::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_physical_sky::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn procedural_textures() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+    let seed = config::config_procedural::NOISE_SEED;
+
+    // 地面：F2 - F1 在细胞边界处接近 0，做成石板缝
+    let stone = NoiseTexture::new(
+        NoiseType::Worley(CellularFeature::F2MinusF1),
+        config::config_procedural::STONE_SCALE,
+        seed,
+    )
+    .with_color_ramp(ColorRamp::new(vec![
+        (0.0, Color::new(0.05, 0.05, 0.05)),
+        (0.08, Color::new(0.35, 0.33, 0.3)),
+        (0.6, Color::new(0.6, 0.58, 0.55)),
+    ]));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(Arc::new(stone))),
+    )));
+
+    // 木纹：低频 Simplex 映射到深浅交替的棕色
+    let wood = NoiseTexture::new(NoiseType::Simplex, config::config_procedural::WOOD_SCALE, seed + 1)
+        .with_octaves(3, 2.0, 0.4)
+        .with_color_ramp(ColorRamp::new(vec![
+            (0.3, Color::new(0.3, 0.15, 0.05)),
+            (0.45, Color::new(0.6, 0.35, 0.15)),
+            (0.55, Color::new(0.35, 0.18, 0.07)),
+            (0.7, Color::new(0.65, 0.4, 0.2)),
+        ]));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(-3.3, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_texture(Arc::new(wood))),
+    )));
+
+    // 细胞：F1 离特征点越远越亮
+    let cells = NoiseTexture::new(
+        NoiseType::Worley(CellularFeature::F1),
+        config::config_procedural::CELL_SCALE,
+        seed + 2,
+    )
+    .with_color_ramp(ColorRamp::new(vec![
+        (0.0, Color::new(0.9, 0.3, 0.2)),
+        (0.7, Color::new(0.95, 0.85, 0.7)),
+    ]));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(-1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_texture(Arc::new(cells))),
+    )));
+
+    // 第二近的距离，图案更圆润
+    let pebbles = NoiseTexture::new(
+        NoiseType::Worley(CellularFeature::F2),
+        config::config_procedural::CELL_SCALE,
+        seed + 3,
+    )
+    .with_color_ramp(ColorRamp::new(vec![
+        (0.2, Color::new(0.25, 0.3, 0.35)),
+        (0.9, Color::new(0.75, 0.78, 0.7)),
+    ]));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(1.1, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_texture(Arc::new(pebbles))),
+    )));
+
+    // 云：扭曲后的 fBm
+    let clouds = NoiseTexture::new(
+        NoiseType::DomainWarped(config::config_procedural::CLOUD_WARP_STRENGTH),
+        config::config_procedural::CLOUD_SCALE,
+        seed + 4,
+    )
+    .with_octaves(5, 2.0, 0.5)
+    .with_color_ramp(ColorRamp::new(vec![
+        (0.35, Color::new(0.2, 0.4, 0.8)),
+        (0.65, Color::new(0.95, 0.95, 0.95)),
+    ]));
+    world.add(Arc::new(Sphere::new_static(
+        Point3::new(3.3, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new_from_texture(Arc::new(clouds))),
+    )));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_procedural::ASPECT_RATIO;
+    cam.image_width = config::config_procedural::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_procedural::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_procedural::MAX_DEPTH;
+    cam.background = config::config_procedural::BACKGROUND;
+    cam.enable_gradient_sky = config::config_procedural::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_procedural::V_FOV;
+    cam.lookfrom = config::config_procedural::LOOKFROM;
+    cam.lookat = config::config_procedural::LOOKAT;
+    cam.vup = config::config_procedural::V_UP;
+
+    cam.defocus_angle = config::config_procedural::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_procedural::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn heterogeneous_media() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_solid_color(Color::new(0.4, 0.45, 0.35)));
+    world.add(Arc::new(Sphere::new_static(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground)));
+
+    // 云：湍流噪声乘上从中心往外的衰减，边缘淡出
+    let cloud_grid = match config::config_heterogeneous_media::CLOUD_GRID_FILE {
+        Some(filename) => DensityGrid::new_from_file(filename)?,
+        None => {
+            let noise = Perlin::new(config::config_heterogeneous_media::CLOUD_SEED);
+            let resolution = config::config_heterogeneous_media::CLOUD_RESOLUTION;
+            DensityGrid::new_from_fn(resolution, resolution, resolution, |local| {
+                let falloff = (1.0 - 2.0 * (local - DVec3::splat(0.5)).length()).max(0.0);
+                let turbulence = noise.turb(config::config_heterogeneous_media::CLOUD_NOISE_SCALE * local, 5, 2.0, 0.5);
+                (2.0 * falloff * turbulence + falloff - 0.2).max(0.0)
+            })
+        }
+    };
+    let cloud_boundary = Arc::new(Sphere::new_static(
+        Point3::new(-1.8, 2.8, 0.0),
+        1.8,
+        Arc::new(Lambertian::new_from_solid_color(Color::ZERO)),
+    ));
+    world.add(Arc::new(GridMedium::new(
+        cloud_boundary,
+        cloud_grid,
+        config::config_heterogeneous_media::CLOUD_DENSITY,
+        config::config_heterogeneous_media::CLOUD_ALBEDO,
+        config::config_heterogeneous_media::CLOUD_PHASE,
+    )));
+
+    // 烟柱：越往上越散、越淡
+    let noise = Perlin::new(config::config_heterogeneous_media::SMOKE_SEED);
+    let resolution = config::config_heterogeneous_media::SMOKE_RESOLUTION;
+    let smoke_grid = DensityGrid::new_from_fn(resolution, 2 * resolution, resolution, |local| {
+        let spread = 0.1 + 0.35 * local.y;
+        let radius = ((local.x - 0.5).powi(2) + (local.z - 0.5).powi(2)).sqrt();
+        let column = (1.0 - radius / spread).max(0.0);
+        let turbulence = noise.turb(config::config_heterogeneous_media::SMOKE_NOISE_SCALE * local, 5, 2.0, 0.5);
+        column * (1.0 - local.y) * (0.3 + turbulence)
+    });
+    let smoke_boundary = Arc::new(Quad::cuboid(
+        Point3::new(1.0, 0.0, -1.0),
+        Point3::new(3.0, 4.0, 1.0),
+        Arc::new(Lambertian::new_from_solid_color(Color::ZERO)),
+    ));
+    world.add(Arc::new(GridMedium::new(
+        smoke_boundary,
+        smoke_grid,
+        config::config_heterogeneous_media::SMOKE_DENSITY,
+        config::config_heterogeneous_media::SMOKE_ALBEDO,
+        config::config_heterogeneous_media::SMOKE_PHASE,
+    )));
+
+    // 玻璃球里的脱脂牛奶，蓝光散射得更多，薄处偏蓝、厚处偏黄
+    let milk_center = Point3::new(0.4, 0.8, 3.0);
+    world.add(Arc::new(Sphere::new_static(
+        milk_center,
+        0.8,
+        Arc::new(Dielectric::new(config::config_heterogeneous_media::MILK_REFRACTION_INDEX)),
+    )));
+    let milk_boundary = Arc::new(Sphere::new_static(milk_center, 0.8, Arc::new(Lambertian::new_from_solid_color(Color::ZERO))));
+    world.add(Arc::new(ConstantMedium::new_chromatic(
+        milk_boundary,
+        config::config_heterogeneous_media::MILK_SIGMA_A / config::config_heterogeneous_media::MILK_UNITS_PER_MM,
+        config::config_heterogeneous_media::MILK_SIGMA_S / config::config_heterogeneous_media::MILK_UNITS_PER_MM,
+        config::config_heterogeneous_media::MILK_PHASE,
+    )));
+
+    // 贴地的薄雾，前向散射让逆光方向更亮
+    let haze_boundary = Arc::new(Quad::cuboid(
+        Point3::new(-20.0, 0.0, -20.0),
+        Point3::new(20.0, 1.5, 20.0),
+        Arc::new(Lambertian::new_from_solid_color(Color::ZERO)),
+    ));
+    world.add(Arc::new(ConstantMedium::new_with_phase(
+        haze_boundary,
+        config::config_heterogeneous_media::HAZE_DENSITY,
+        config::config_heterogeneous_media::HAZE_ALBEDO,
+        config::config_heterogeneous_media::HAZE_PHASE,
+    )));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_heterogeneous_media::ASPECT_RATIO;
+    cam.image_width = config::config_heterogeneous_media::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_heterogeneous_media::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_heterogeneous_media::MAX_DEPTH;
+    cam.background = config::config_heterogeneous_media::BACKGROUND;
+    cam.enable_gradient_sky = config::config_heterogeneous_media::ENABLE_GRADIENT_SKY;
+    cam.lights = vec![Arc::new(DirectionalLight::new(
+        config::config_heterogeneous_media::SUN_DIRECTION,
+        config::config_heterogeneous_media::SUN_IRRADIANCE,
+    ))];
+
+    cam.vfov = config::config_heterogeneous_media::V_FOV;
+    cam.lookfrom = config::config_heterogeneous_media::LOOKFROM;
+    cam.lookat = config::config_heterogeneous_media::LOOKAT;
+    cam.vup = config::config_heterogeneous_media::V_UP;
+
+    cam.defocus_angle = config::config_heterogeneous_media::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_heterogeneous_media::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn primitives() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let uv_checker = |c1: Color, c2: Color| -> Arc<Lambertian> {
+        let count = config::config_primitives::UV_CHECKER_COUNT;
+        Arc::new(Lambertian::new_from_texture(Arc::new(UvCheckerTexture::new(count, count, c1, c2))))
+    };
+    let white = Color::new(0.9, 0.9, 0.9);
+
+    // 无限大的地面，不放进 BVH
+    let ground = Arc::new(Lambertian::new_from_texture(Arc::new(CheckerTexture::new(
+        0.5,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ))));
+    world.add(Arc::new(Plane::new(Point3::ZERO, DVec3::new(0.0, 0.0, 1.0), DVec3::new(1.0, 0.0, 0.0), ground)));
+
+    let mut objects = HittableList::new();
+    objects.add(Arc::new(Cylinder::new(
+        Point3::new(-3.2, 0.0, 0.0),
+        DVec3::new(0.0, 1.8, 0.0),
+        0.7,
+        true,
+        uv_checker(Color::new(0.8, 0.2, 0.2), white),
+    )));
+    objects.add(Arc::new(Cone::new(
+        Point3::new(-1.3, 0.0, 0.0),
+        DVec3::new(0.0, 2.0, 0.0),
+        0.8,
+        0.0,
+        true,
+        uv_checker(Color::new(0.9, 0.6, 0.1), white),
+    )));
+    objects.add(Arc::new(Capsule::new(
+        Point3::new(0.6, 0.5, 0.6),
+        Point3::new(1.0, 1.8, -0.6),
+        0.45,
+        uv_checker(Color::new(0.2, 0.6, 0.2), white),
+    )));
+    objects.add(Arc::new(Torus::new(
+        Point3::new(3.0, 0.9, 0.0),
+        DVec3::new(0.4, 1.0, 0.6),
+        0.8,
+        0.3,
+        uv_checker(Color::new(0.2, 0.3, 0.8), white),
+    )));
+
+    // 两端开口的金属管子，能看到里面；管子后面立着一个圆盘
+    objects.add(Arc::new(Cylinder::new(
+        Point3::new(-2.0, 0.35, 2.2),
+        DVec3::new(1.6, 0.0, -0.4),
+        0.35,
+        false,
+        Arc::new(Metal::new(Color::new(0.8, 0.8, 0.85), 0.05)),
+    )));
+    objects.add(Arc::new(Ellipse::disk(
+        Point3::new(0.0, 1.0, -2.5),
+        DVec3::new(0.0, 0.2, 1.0),
+        1.0,
+        uv_checker(Color::new(0.6, 0.2, 0.7), white),
+    )));
+    world.add(Arc::new(BvhNode::new(objects)));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_primitives::ASPECT_RATIO;
+    cam.image_width = config::config_primitives::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_primitives::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_primitives::MAX_DEPTH;
+    cam.background = config::config_primitives::BACKGROUND;
+    cam.enable_gradient_sky = config::config_primitives::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_primitives::V_FOV;
+    cam.lookfrom = config::config_primitives::LOOKFROM;
+    cam.lookat = config::config_primitives::LOOKAT;
+    cam.vup = config::config_primitives::V_UP;
+
+    cam.defocus_angle = config::config_primitives::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_primitives::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn constructive_solids() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_texture(Arc::new(CheckerTexture::new(
+        0.3,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ))));
+    world.add(Arc::new(Plane::new(Point3::ZERO, DVec3::new(0.0, 0.0, 1.0), DVec3::new(1.0, 0.0, 0.0), ground)));
+
+    // 凸透镜：两个球的交集
+    let glass = Arc::new(Dielectric::new(config::config_csg::LENS_REFRACTION_INDEX));
+    world.add(Arc::new(Csg::new(
+        CsgOperation::Intersection,
+        Arc::new(Sphere::new_static(Point3::new(-3.0, 1.3, -1.7), 2.0, glass.clone())),
+        Arc::new(Sphere::new_static(Point3::new(-3.0, 1.3, 1.7), 2.0, glass)),
+    )));
+
+    // 挖空的盒子：立方体减去比它稍大的球，每个面上留下一个圆洞
+    let orange = Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.45, 0.15)));
+    world.add(Arc::new(Csg::new(
+        CsgOperation::Difference,
+        Arc::new(Quad::cuboid(Point3::new(-0.9, 0.0, -0.9), Point3::new(0.9, 1.8, 0.9), orange.clone())),
+        Arc::new(Sphere::new_static(Point3::new(0.0, 0.9, 0.0), 1.15, orange)),
+    )));
+
+    // 机加工零件：带中心通孔和侧面缺口的圆盘，上面再加一个凸台
+    let steel = Arc::new(Metal::new(Color::new(0.75, 0.75, 0.8), 0.2));
+    let flange = Arc::new(Csg::new(
+        CsgOperation::Difference,
+        Arc::new(Cylinder::new(Point3::new(3.0, 0.0, 0.0), DVec3::new(0.0, 0.5, 0.0), 1.1, true, steel.clone())),
+        Arc::new(Quad::cuboid(Point3::new(3.6, -0.1, -0.25), Point3::new(4.2, 0.6, 0.25), steel.clone())),
+    ));
+    let boss = Arc::new(Csg::new(
+        CsgOperation::Union,
+        flange,
+        Arc::new(Cylinder::new(Point3::new(3.0, 0.0, 0.0), DVec3::new(0.0, 1.2, 0.0), 0.6, true, steel.clone())),
+    ));
+    world.add(Arc::new(Csg::new(
+        CsgOperation::Difference,
+        boss,
+        Arc::new(Cylinder::new(Point3::new(3.0, -0.1, 0.0), DVec3::new(0.0, 1.4, 0.0), 0.35, true, steel)),
+    )));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_csg::ASPECT_RATIO;
+    cam.image_width = config::config_csg::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_csg::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_csg::MAX_DEPTH;
+    cam.background = config::config_csg::BACKGROUND;
+    cam.enable_gradient_sky = config::config_csg::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_csg::V_FOV;
+    cam.lookfrom = config::config_csg::LOOKFROM;
+    cam.lookat = config::config_csg::LOOKAT;
+    cam.vup = config::config_csg::V_UP;
+
+    cam.defocus_angle = config::config_csg::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_csg::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn distance_fields() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_texture(Arc::new(CheckerTexture::new(
+        0.3,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ))));
+    world.add(Arc::new(Plane::new(Point3::ZERO, DVec3::new(0.0, 0.0, 1.0), DVec3::new(1.0, 0.0, 0.0), ground)));
+
+    // 分形
+    let bulb_center = Point3::new(-3.0, 1.3, 0.0);
+    world.add(Arc::new(Sdf::new(
+        sdf::mandelbulb(
+            bulb_center,
+            1.0,
+            config::config_sdf::MANDELBULB_POWER,
+            config::config_sdf::MANDELBULB_ITERATIONS,
+        ),
+        Aabb::new_from_two_points(bulb_center - DVec3::splat(1.25), bulb_center + DVec3::splat(1.25)),
+        Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.5, 0.3))),
+    )));
+
+    // 几个球和圆环平滑地融在一起
+    let k = config::config_sdf::BLEND_SMOOTHNESS;
+    let blob = sdf::smooth_union(
+        sdf::smooth_union(
+            sdf::sphere(Point3::new(-0.4, 0.8, 0.0), 0.7),
+            sdf::sphere(Point3::new(0.5, 1.2, 0.2), 0.5),
+            k,
+        ),
+        sdf::torus(Point3::new(0.0, 0.5, 0.0), 0.9, 0.2),
+        k,
+    );
+    world.add(Arc::new(Sdf::new(
+        blob,
+        Aabb::new_from_two_points(Point3::new(-1.4, 0.0, -1.4), Point3::new(1.4, 2.0, 1.4)),
+        Arc::new(Dielectric::new(1.5)),
+    )));
+
+    // 圆角方块上平滑地挖掉一个球，正面再铣出一道槽
+    let block_center = Point3::new(3.0, 0.8, 0.0);
+    let block = sdf::smooth_subtraction(
+        sdf::smooth_subtraction(
+            sdf::round_box(block_center, DVec3::splat(0.8), 0.15),
+            sdf::sphere(block_center + DVec3::new(0.0, 0.8, 0.0), 0.6),
+            k,
+        ),
+        sdf::cuboid(block_center + DVec3::new(0.0, 0.0, 0.8), DVec3::new(1.0, 0.15, 0.2)),
+        0.5 * k,
+    );
+    world.add(Arc::new(Sdf::new(
+        block,
+        Aabb::new_from_two_points(block_center - DVec3::splat(0.8), block_center + DVec3::splat(0.8)),
+        Arc::new(Metal::new(Color::new(0.75, 0.75, 0.8), 0.1)),
+    )));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_sdf::ASPECT_RATIO;
+    cam.image_width = config::config_sdf::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_sdf::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_sdf::MAX_DEPTH;
+    cam.background = config::config_sdf::BACKGROUND;
+    cam.enable_gradient_sky = config::config_sdf::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_sdf::V_FOV;
+    cam.lookfrom = config::config_sdf::LOOKFROM;
+    cam.lookat = config::config_sdf::LOOKAT;
+    cam.vup = config::config_sdf::V_UP;
+
+    cam.defocus_angle = config::config_sdf::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_sdf::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn terrain() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(match config::config_terrain::TERRAIN_COLOR_IMAGE {
+        Some(filename) => Lambertian::new_from_texture(Arc::new(ImageTexture::new(filename)?)),
+        None => Lambertian::new_from_solid_color(Color::new(0.45, 0.42, 0.3)),
+    });
+    let origin = config::config_terrain::TERRAIN_ORIGIN;
+    let size = config::config_terrain::TERRAIN_SIZE;
+    let heightfield = match config::config_terrain::TERRAIN_HEIGHT_IMAGE {
+        Some(filename) => Heightfield::new_from_image(filename, origin, size, ground)?,
+        None => {
+            // 扭曲过的 fBm 有山脊和山谷的样子
+            let heights = NoiseTexture::new(
+                NoiseType::DomainWarped(1.5),
+                config::config_terrain::TERRAIN_NOISE_SCALE,
+                config::config_terrain::TERRAIN_NOISE_SEED,
+            )
+            .with_octaves(6, 2.0, 0.45);
+            let resolution = config::config_terrain::TERRAIN_RESOLUTION;
+            Heightfield::new_from_texture(&heights, resolution, resolution, origin, size, ground)
+        }
+    };
+    world.add(Arc::new(heightfield));
+
+    // 水面
+    let water = Arc::new(Metal::new(Color::new(0.35, 0.5, 0.6), 0.05));
+    world.add(Arc::new(Plane::new(
+        Point3::new(0.0, config::config_terrain::WATER_LEVEL, 0.0),
+        DVec3::new(0.0, 0.0, 1.0),
+        DVec3::new(1.0, 0.0, 0.0),
+        water,
+    )));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_terrain::ASPECT_RATIO;
+    cam.image_width = config::config_terrain::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_terrain::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_terrain::MAX_DEPTH;
+    cam.background = config::config_terrain::BACKGROUND;
+    cam.enable_gradient_sky = config::config_terrain::ENABLE_GRADIENT_SKY;
+    cam.lights = vec![Arc::new(DirectionalLight::new(
+        config::config_terrain::SUN_DIRECTION,
+        config::config_terrain::SUN_IRRADIANCE,
+    ))];
+
+    cam.vfov = config::config_terrain::V_FOV;
+    cam.lookfrom = config::config_terrain::LOOKFROM;
+    cam.lookat = config::config_terrain::LOOKAT;
+    cam.vup = config::config_terrain::V_UP;
+
+    cam.defocus_angle = config::config_terrain::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_terrain::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn subdivision() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::new_from_texture(Arc::new(CheckerTexture::new(
+        0.3,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ))));
+    world.add(Arc::new(Plane::new(Point3::ZERO, DVec3::new(0.0, 0.0, 1.0), DVec3::new(1.0, 0.0, 0.0), ground)));
+
+    // 上下两条边朝相反方向倾斜的双线性曲面片，四个角不共面
+    let twisted = Arc::new(Lambertian::new_from_texture(Arc::new(UvCheckerTexture::new(
+        8.0,
+        8.0,
+        Color::new(0.9, 0.9, 0.9),
+        Color::new(0.2, 0.3, 0.7),
+    ))));
+    world.add(Arc::new(BilinearPatch::new(
+        Point3::new(-4.5, 0.2, 0.0),
+        Point3::new(-2.5, 0.2, -1.2),
+        Point3::new(-4.5, 2.2, -1.2),
+        Point3::new(-2.5, 2.2, 0.0),
+        twisted,
+    )));
+
+    let levels = config::config_subdivision::SUBDIVISION_LEVELS;
+
+    // Catmull–Clark：顶面向上挤出一截的立方体笼子
+    let cage = ControlMesh::new(
+        vec![
+            Point3::new(-1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, -1.0),
+            Point3::new(1.0, 0.0, 1.0),
+            Point3::new(-1.0, 0.0, 1.0),
+            Point3::new(-1.0, 1.6, -1.0),
+            Point3::new(1.0, 1.6, -1.0),
+            Point3::new(1.0, 1.6, 1.0),
+            Point3::new(-1.0, 1.6, 1.0),
+            Point3::new(-0.4, 3.0, -0.4),
+            Point3::new(0.4, 3.0, -0.4),
+            Point3::new(0.4, 3.0, 0.4),
+            Point3::new(-0.4, 3.0, 0.4),
+        ],
+        vec![
+            vec![0, 1, 2, 3],
+            vec![3, 2, 6, 7],
+            vec![2, 1, 5, 6],
+            vec![1, 0, 4, 5],
+            vec![0, 3, 7, 4],
+            vec![7, 6, 10, 11],
+            vec![6, 5, 9, 10],
+            vec![5, 4, 8, 9],
+            vec![4, 7, 11, 8],
+            vec![8, 11, 10, 9],
+        ],
+    );
+    let clay = Arc::new(Lambertian::new_from_solid_color(Color::new(0.8, 0.35, 0.25)));
+    let smooth = cage.subdivide(SubdivisionScheme::CatmullClark, levels).triangulate(clay);
+    world.add(Arc::new(Translate::new(Arc::new(BvhNode::new(smooth)), DVec3::new(0.0, -0.1, 0.0))));
+
+    // Loop：拉长的八面体
+    let octahedron = ControlMesh::new(
+        vec![
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(-1.0, 0.0, 0.0),
+            Point3::new(0.0, 1.6, 0.0),
+            Point3::new(0.0, -1.6, 0.0),
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, -1.0),
+        ],
+        vec![
+            vec![0, 2, 4],
+            vec![4, 2, 1],
+            vec![1, 2, 5],
+            vec![5, 2, 0],
+            vec![0, 4, 3],
+            vec![4, 1, 3],
+            vec![1, 5, 3],
+            vec![5, 0, 3],
+        ],
+    );
+    let gold = Arc::new(Metal::new(Color::new(0.9, 0.7, 0.3), 0.05));
+    let smooth = octahedron.subdivide(SubdivisionScheme::Loop, levels).triangulate(gold);
+    world.add(Arc::new(Translate::new(Arc::new(BvhNode::new(smooth)), DVec3::new(3.5, 1.0, 0.0))));
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_subdivision::ASPECT_RATIO;
+    cam.image_width = config::config_subdivision::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_subdivision::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_subdivision::MAX_DEPTH;
+    cam.background = config::config_subdivision::BACKGROUND;
+    cam.enable_gradient_sky = config::config_subdivision::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_subdivision::V_FOV;
+    cam.lookfrom = config::config_subdivision::LOOKFROM;
+    cam.lookat = config::config_subdivision::LOOKAT;
+    cam.vup = config::config_subdivision::V_UP;
+
+    cam.defocus_angle = config::config_subdivision::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_subdivision::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
+fn texture_sampling() -> anyhow::Result<()> {
+    let mut world = HittableList::new();
+    let image = Arc::new(ImageTexture::new(config::config_texture_sampling::TEXTURE_IMAGE)?);
+
+    // 上排：同一张图缩小后居中，比较三种超出 [0, 1] 时的处理方式
+    let tiling = config::config_texture_sampling::WRAP_TILING;
+    let wrap_modes = [
+        WrapMode::Repeat,
+        WrapMode::Mirror,
+        WrapMode::Border(config::config_texture_sampling::BORDER_COLOR),
+    ];
+    for (k, wrap) in wrap_modes.into_iter().enumerate() {
+        let tex = Arc::new(UvTransform::new(image.clone(), tiling, -0.5 * (tiling - DVec2::ONE), 0.0, wrap));
+        world.add(Arc::new(Quad::new(
+            Point3::new(-5.2 + 3.6 * k as f64, 0.2, 0.0),
+            DVec3::new(3.2, 0.0, 0.0),
+            DVec3::new(0.0, 1.8, 0.0),
+            Arc::new(Lambertian::new_from_texture(tex)),
+        )));
+    }
+
+    // 下排：放大很小的一块，比较三种过滤方式
+    let filters = [FilterMode::Nearest, FilterMode::Bilinear, FilterMode::Bicubic];
+    for (k, filter) in filters.into_iter().enumerate() {
+        let filtered = Arc::new(ImageTexture::new_with_options(
+            config::config_texture_sampling::TEXTURE_IMAGE,
+            WrapMode::Clamp,
+            filter,
+            ColorSpace::Srgb,
+        )?);
+        let tex = Arc::new(UvTransform::new(
+            filtered,
+            config::config_texture_sampling::CROP_SCALE,
+            config::config_texture_sampling::CROP_OFFSET,
+            0.0,
+            WrapMode::Clamp,
+        ));
+        world.add(Arc::new(Quad::new(
+            Point3::new(-5.2 + 3.6 * k as f64, -2.0, 0.0),
+            DVec3::new(3.2, 0.0, 0.0),
+            DVec3::new(0.0, 1.8, 0.0),
+            Arc::new(Lambertian::new_from_texture(tex)),
+        )));
+    }
+
+    let mut cam = Camera::default();
+    cam.aspect_ratio = config::config_texture_sampling::ASPECT_RATIO;
+    cam.image_width = config::config_texture_sampling::IMAGE_WIDTH;
+    cam.samples_per_pixel = config::config_texture_sampling::SAMPLES_PER_PIXEL;
+    cam.max_depth = config::config_texture_sampling::MAX_DEPTH;
+    cam.background = config::config_texture_sampling::BACKGROUND;
+    cam.enable_gradient_sky = config::config_texture_sampling::ENABLE_GRADIENT_SKY;
+
+    cam.vfov = config::config_texture_sampling::V_FOV;
+    cam.lookfrom = config::config_texture_sampling::LOOKFROM;
+    cam.lookat = config::config_texture_sampling::LOOKAT;
+    cam.vup = config::config_texture_sampling::V_UP;
+
+    cam.defocus_angle = config::config_texture_sampling::DEFOCUS_ANGLE;
+    cam.focus_dist = config::config_texture_sampling::FOCUS_DIST;
+
+    cam.render(&world)
+}
+
 fn final_scene(image_width: usize, samples_per_pixel: usize, max_depth: usize) -> anyhow::Result<()> {
 
     // 地面绿色盒子阵列，高度随机
@@ -493,20 +1556,20 @@ fn final_scene(image_width: usize, samples_per_pixel: usize, max_depth: usize) -
     world.add(Arc::new(Sphere::new_static(Point3::new(0.0, 150.0, 145.0), 50.0, Arc::new(Metal::new(Color::new(0.8, 0.8, 0.9), 1.0)))));
 
     // 蓝色玻璃浓雾球
-    let mut boundary = Arc::new(Sphere::new_static(Point3::new(360.0, 150.0, 145.0), 70.0, Arc::new(Dielectric::new(1.5))));
+    let boundary = Arc::new(Sphere::new_static(Point3::new(360.0, 150.0, 145.0), 70.0, Arc::new(Dielectric::new(1.5))));
     world.add(boundary.clone());
     world.add(Arc::new(ConstantMedium::new_from_solid_color(boundary, 0.2, Color::new(0.2, 0.4, 0.9))));
-    
-    // 全局白色薄雾
-    boundary = Arc::new(Sphere::new_static(Point3::new(0.0, 0.0, 0.0), 5000.0, Arc::new(Dielectric::new(1.5))));
-    world.add(Arc::new(ConstantMedium::new_from_solid_color(boundary, 0.0001, Color::new(1.0, 1.0, 1.0))));
 
     // 地球
     let emat = Arc::new(Lambertian::new_from_texture(Arc::new(ImageTexture::new("earthmap.jpg")?)));
     world.add(Arc::new(Sphere::new_static(Point3::new(400.0, 200.0, 400.0), 100.0, emat)));
     
     // 噪声纹理球
-    let perlin_texture = Arc::new(NoiseTexture::new(config::config_final_scene::INPUT_POINT_SCALE));
+    let perlin_texture = Arc::new(NoiseTexture::new(
+        config::config_final_scene::NOISE_TYPE,
+        config::config_final_scene::INPUT_POINT_SCALE,
+        config::config_final_scene::NOISE_SEED,
+    ));
     world.add(Arc::new(Sphere::new_static(Point3::new(220.0, 280.0, 300.0), 80.0, Arc::new(Lambertian::new_from_texture(perlin_texture)))));
 
     // 随机小球群组成的立方体
@@ -528,6 +1591,18 @@ fn final_scene(image_width: usize, samples_per_pixel: usize, max_depth: usize) -
     cam.max_depth = max_depth;
     cam.background = config::config_final_scene::BACKGROUND;
     cam.enable_gradient_sky = config::config_final_scene::ENABLE_GRADIENT_SKY;
+    // 全局白色薄雾
+    cam.medium = Some(Arc::new(
+        Atmosphere::new(
+            config::config_final_scene::ATMOSPHERE_DENSITY,
+            config::config_final_scene::ATMOSPHERE_ALBEDO,
+            PhaseFunction::Isotropic,
+        )
+        .with_height_falloff(
+            config::config_final_scene::ATMOSPHERE_HEIGHT_FALLOFF,
+            config::config_final_scene::ATMOSPHERE_BASE_HEIGHT,
+        ),
+    ));
 
     cam.vfov = config::config_final_scene::V_FOV;
     cam.lookfrom = config::config_final_scene::LOOKFROM;
@@ -551,6 +1626,18 @@ fn main() {
         Scene::SimpleLight => simple_light(),
         Scene::CornellBox => cornell_box(),
         Scene::CornellSmoke => cornell_smoke(),
+        Scene::Materials => materials(),
+        Scene::DeltaLights => delta_lights(),
+        Scene::EnvironmentLighting => environment_lighting(),
+        Scene::PhysicalSky => physical_sky(),
+        Scene::ProceduralTextures => procedural_textures(),
+        Scene::HeterogeneousMedia => heterogeneous_media(),
+        Scene::Primitives => primitives(),
+        Scene::ConstructiveSolids => constructive_solids(),
+        Scene::DistanceFields => distance_fields(),
+        Scene::Terrain => terrain(),
+        Scene::Subdivision => subdivision(),
+        Scene::TextureSampling => texture_sampling(),
         Scene::FinalSceneLD => final_scene(config::config_final_scene::IMAGE_WIDTH_LD, config::config_final_scene::SAMPLES_PER_PIXEL_LD, config::config_final_scene::MAX_DEPTH_LD),
         Scene::FinalSceneHD => final_scene(config::config_final_scene::IMAGE_WIDTH_HD, config::config_final_scene::SAMPLES_PER_PIXEL_HD, config::config_final_scene::MAX_DEPTH_HD),
     };
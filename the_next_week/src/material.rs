@@ -1,19 +1,58 @@
+use std::f64::consts::PI;
 use std::sync::Arc;
 
 use glam::DVec3;
 
 use crate::color::Color;
+use crate::constant;
 use crate::dvec3::DVec3Ext;
+use crate::enums::{EmissionProfile, PhaseFunction};
 use crate::hit_record::HitRecord;
 use crate::point3::Point3;
+use crate::random_number_generator::random;
 use crate::ray::Ray;
 use crate::texture::{SolidColor, Texture};
 
+pub struct ScatterRecord {
+    pub attenuation: Color,
+    pub scattered: Ray,
+    // 散射方向来自 eval 覆盖不到的镜面分布（反射、折射、直接穿过等），
+    // 光源直接采样照不到这类方向，光照只能靠这条散射光线自己带回来
+    pub is_specular: bool,
+}
+
+impl ScatterRecord {
+    pub fn diffuse(attenuation: Color, scattered: Ray) -> ScatterRecord {
+        ScatterRecord {
+            attenuation,
+            scattered,
+            is_specular: false,
+        }
+    }
+
+    pub fn specular(attenuation: Color, scattered: Ray) -> ScatterRecord {
+        ScatterRecord {
+            attenuation,
+            scattered,
+            is_specular: true,
+        }
+    }
+}
+
 pub trait Material: Send + Sync {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)>;
-    fn emitted(&self, _u: f64, _v: f64, _p: Point3) -> Option<Color> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord>;
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<Color> {
+        None
+    }
+    /// 光线从 direction（单位向量）方向照到交点后，朝 -r_in 方向散射的 BRDF * cos，用于点光源这类打不中的光源。
+    /// 镜面类材质只能沿 scatter 的方向接收光照，返回 None
+    fn eval(&self, _r_in: &Ray, _rec: &HitRecord, _direction: DVec3) -> Option<Color> {
         None
     }
+    /// 阴影光线在交点处被挡住的比例，0 为完全透明，1 为完全不透明。镂空材质按遮罩返回中间值
+    fn opacity(&self, _rec: &HitRecord) -> f64 {
+        1.0
+    }
 }
 
 pub struct Lambertian {
@@ -33,14 +72,135 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let mut scatter_direction = rec.unit_normal + DVec3::random_unit();
         if scatter_direction.near_zero() {
             scatter_direction = rec.unit_normal;
         }
         let scattered = Ray::new_with_time(rec.p, scatter_direction, r_in.time());
-        let attenuation = self.tex.value(rec.u, rec.v, rec.p);
-        Some((attenuation, scattered))
+        let attenuation = self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint());
+        Some(ScatterRecord::diffuse(attenuation, scattered))
+    }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let cos_theta = direction.dot(rec.unit_normal).max(0.0);
+        Some(self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) * cos_theta / PI)
+    }
+}
+
+/// Oren–Nayar 粗糙漫反射，适合陶土、布料这类逆光方向更亮的表面。
+/// sigma 是微表面朝向分布的标准差（角度制），为 0 时退化成 Lambertian
+pub struct OrenNayar {
+    tex: Arc<dyn Texture>,
+    a: f64,
+    b: f64,
+}
+
+impl OrenNayar {
+    pub fn new_from_solid_color(albedo: Color, sigma: f64) -> OrenNayar {
+        OrenNayar::new_from_texture(Arc::new(SolidColor::new(albedo)), sigma)
+    }
+
+    pub fn new_from_texture(tex: Arc<dyn Texture>, sigma: f64) -> OrenNayar {
+        let sigma2 = sigma.to_radians().powi(2);
+        OrenNayar {
+            tex,
+            a: 1.0 - sigma2 / (2.0 * (sigma2 + 0.33)),
+            b: 0.45 * sigma2 / (sigma2 + 0.09),
+        }
+    }
+
+    // Oren–Nayar BRDF 相对 Lambertian 的比例 A + B * max(0, cos(phi_i - phi_o)) * sin(alpha) * tan(beta)
+    fn roughness_factor(&self, wo: DVec3, wi: DVec3, normal: DVec3) -> f64 {
+        let cos_theta_o = wo.dot(normal).clamp(0.0, 1.0);
+        let cos_theta_i = wi.dot(normal).clamp(0.0, 1.0);
+        let sin_theta_o = (1.0 - cos_theta_o * cos_theta_o).sqrt();
+        let sin_theta_i = (1.0 - cos_theta_i * cos_theta_i).sqrt();
+
+        let mut max_cos = 0.0;
+        if sin_theta_o > constant::NEAR_ZERO_THRESHOLD && sin_theta_i > constant::NEAR_ZERO_THRESHOLD {
+            // 两个方向投影到切平面后夹角的余弦，即 cos(phi_i - phi_o)
+            let tangent_o = (wo - cos_theta_o * normal) / sin_theta_o;
+            let tangent_i = (wi - cos_theta_i * normal) / sin_theta_i;
+            max_cos = tangent_o.dot(tangent_i).max(0.0);
+        }
+
+        // alpha = max(theta_i, theta_o), beta = min(theta_i, theta_o)
+        let (sin_alpha, tan_beta) = if cos_theta_i > cos_theta_o {
+            (sin_theta_o, sin_theta_i / cos_theta_i)
+        } else {
+            (sin_theta_i, sin_theta_o / cos_theta_o.max(constant::NEAR_ZERO_THRESHOLD))
+        };
+        self.a + self.b * max_cos * sin_alpha * tan_beta
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let mut scatter_direction = rec.unit_normal + DVec3::random_unit();
+        if scatter_direction.near_zero() {
+            scatter_direction = rec.unit_normal;
+        }
+        let scattered = Ray::new_with_time(rec.p, scatter_direction, r_in.time());
+
+        // 按余弦分布采样，BRDF * cos / pdf 化简后只剩 albedo * (A + B * ...)
+        let wo = -r_in.direction().normalize();
+        let attenuation = self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) * self.roughness_factor(wo, scatter_direction.normalize(), rec.unit_normal);
+        Some(ScatterRecord::diffuse(attenuation, scattered))
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let cos_theta = direction.dot(rec.unit_normal);
+        if cos_theta <= 0.0 {
+            return Some(Color::ZERO);
+        }
+        let wo = -r_in.direction().normalize();
+        let factor = self.roughness_factor(wo, direction, rec.unit_normal);
+        Some(self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) * factor * cos_theta / PI)
+    }
+}
+
+/// 漫透射材质，模拟纸张、灯罩这类薄的半透明片。
+/// 以 transmittance 的概率散射到背面半球，否则像 Lambertian 一样在正面漫反射
+pub struct DiffuseTransmission {
+    tex: Arc<dyn Texture>,
+    transmittance: f64,
+}
+
+impl DiffuseTransmission {
+    pub fn new_from_solid_color(albedo: Color, transmittance: f64) -> DiffuseTransmission {
+        DiffuseTransmission::new_from_texture(Arc::new(SolidColor::new(albedo)), transmittance)
+    }
+
+    pub fn new_from_texture(tex: Arc<dyn Texture>, transmittance: f64) -> DiffuseTransmission {
+        DiffuseTransmission {
+            tex,
+            transmittance: transmittance.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Material for DiffuseTransmission {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        // unit_normal 总是朝向入射一侧，取反就是背面半球
+        let normal = if random() < self.transmittance {
+            -rec.unit_normal
+        } else {
+            rec.unit_normal
+        };
+        let mut scatter_direction = normal + DVec3::random_unit();
+        if scatter_direction.near_zero() {
+            scatter_direction = normal;
+        }
+        let scattered = Ray::new_with_time(rec.p, scatter_direction, r_in.time());
+        let attenuation = self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint());
+        Some(ScatterRecord::diffuse(attenuation, scattered))
+    }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let cos_theta = direction.dot(rec.unit_normal);
+        let lobe = if cos_theta < 0.0 { self.transmittance } else { 1.0 - self.transmittance };
+        Some(self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) * lobe * cos_theta.abs() / PI)
     }
 }
 
@@ -57,13 +217,13 @@ impl Metal {
     }
 }
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let mut reflected = r_in.direction().reflect(rec.unit_normal);
         reflected = reflected.normalize() + self.fuzz * DVec3::random_unit();
         let scattered = Ray::new_with_time(rec.p, reflected, r_in.time());
         let attenuation = self.albedo;
         if scattered.direction().dot(rec.unit_normal) > 0. {
-            Some((attenuation, scattered))
+            Some(ScatterRecord::specular(attenuation, scattered))
         } else {
             None
         }
@@ -82,7 +242,7 @@ impl Dielectric {
     }
 
     // 反射比，Use Schlick's approximation for reflectance.
-    fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
+    pub fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
         let mut r0 = (1. - refraction_index) / (1. + refraction_index);
         r0 = r0 * r0;
         r0 + (1. - r0) * (1. - cosine).powi(5)
@@ -90,7 +250,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let attenuation = Color::ONE;
         let ri = if rec.front_face {
             1. / self.refraction_index
@@ -112,33 +272,385 @@ impl Material for Dielectric {
             };
 
         let scattered = Ray::new_with_time(rec.p, r_out_direction, r_in.time());
-        Some((attenuation, scattered))
+        Some(ScatterRecord::specular(attenuation, scattered))
+    }
+}
+
+/// 清漆涂层：在任意基底材质外面包一层电介质，比如车漆、上过漆的木头。
+/// 在涂层表面按 Fresnel 反射率随机决定是镜面（或带粗糙度的）反射，还是穿过涂层交给基底散射；
+/// 穿过涂层的光线进出各走一次 thickness，按 absorption 做 Beer–Lambert 衰减，出射时再乘上透射率
+pub struct Coated {
+    base: Arc<dyn Material>,
+    refraction_index: f64,
+    roughness: f64,
+    thickness: f64,
+    absorption: Color, // 涂层每单位厚度的吸收系数，按通道
+}
+
+impl Coated {
+    pub fn new(base: Arc<dyn Material>, refraction_index: f64, roughness: f64, thickness: f64, absorption: Color) -> Coated {
+        Coated {
+            base,
+            refraction_index,
+            roughness: roughness.clamp(0.0, 1.0),
+            thickness: thickness.max(0.0),
+            absorption,
+        }
+    }
+
+    // 光线在涂层内部和法线夹角的余弦
+    fn cos_inside_coat(&self, cos_theta: f64) -> f64 {
+        let sin2_theta_t = (1.0 - cos_theta * cos_theta) / (self.refraction_index * self.refraction_index);
+        (1.0 - sin2_theta_t).max(0.0).sqrt()
+    }
+}
+
+impl Material for Coated {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        // 从背面打进来（例如基底是透明材质时在内部的光线），涂层不起作用
+        if !rec.front_face {
+            return self.base.scatter(r_in, rec);
+        }
+
+        let unit_r_in_direction = r_in.direction().normalize();
+        let cos_in = (-unit_r_in_direction).dot(rec.unit_normal).min(1.0);
+        if Dielectric::reflectance(cos_in, 1.0 / self.refraction_index) > random() {
+            let reflected = unit_r_in_direction.reflect(rec.unit_normal) + self.roughness * DVec3::random_unit();
+            if reflected.dot(rec.unit_normal) <= 0.0 {
+                return None;
+            }
+            return Some(ScatterRecord::specular(Color::ONE, Ray::new_with_time(rec.p, reflected, r_in.time())));
+        }
+
+        let mut srec = self.base.scatter(r_in, rec)?;
+        let cos_out = srec.scattered.direction().normalize().dot(rec.unit_normal);
+        if cos_out <= 0.0 {
+            // 基底把光线折射进物体内部，不再穿出涂层
+            return Some(srec);
+        }
+
+        let path_length = self.thickness * (1.0 / self.cos_inside_coat(cos_in) + 1.0 / self.cos_inside_coat(cos_out));
+        let coat_transmittance = Color::new(
+            (-self.absorption.x * path_length).exp(),
+            (-self.absorption.y * path_length).exp(),
+            (-self.absorption.z * path_length).exp(),
+        );
+        let exit_transmittance = 1.0 - Dielectric::reflectance(cos_out, 1.0 / self.refraction_index);
+        srec.attenuation *= coat_transmittance * exit_transmittance;
+        Some(srec)
+    }
+
+    // 只计算穿过涂层到达基底的部分，涂层表面的镜面反射照不到点光源
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let base = self.base.eval(r_in, rec, direction)?;
+        let cos_out = direction.dot(rec.unit_normal);
+        if !rec.front_face || cos_out <= 0.0 {
+            return Some(base);
+        }
+        let cos_in = (-r_in.direction().normalize()).dot(rec.unit_normal).min(1.0);
+        let path_length = self.thickness * (1.0 / self.cos_inside_coat(cos_in) + 1.0 / self.cos_inside_coat(cos_out));
+        let coat_transmittance = Color::new(
+            (-self.absorption.x * path_length).exp(),
+            (-self.absorption.y * path_length).exp(),
+            (-self.absorption.z * path_length).exp(),
+        );
+        let fresnel_transmittance = (1.0 - Dielectric::reflectance(cos_in, 1.0 / self.refraction_index))
+            * (1.0 - Dielectric::reflectance(cos_out, 1.0 / self.refraction_index));
+        Some(base * coat_transmittance * fresnel_transmittance)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        self.base.emitted(r_in, rec)
+    }
+}
+
+/// 用纹理当权重在两种材质之间随机挑选，权重取纹理颜色三个通道的平均值，1 表示完全使用 first
+pub struct MixMaterial {
+    first: Arc<dyn Material>,
+    second: Arc<dyn Material>,
+    weight: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: Arc<dyn Texture>) -> MixMaterial {
+        MixMaterial {
+            first,
+            second,
+            weight,
+        }
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        if random() < mask_value(self.weight.as_ref(), rec.u, rec.v, rec.p) {
+            self.first.scatter(r_in, rec)
+        } else {
+            self.second.scatter(r_in, rec)
+        }
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        let w = mask_value(self.weight.as_ref(), rec.u, rec.v, rec.p);
+        match (self.first.emitted(r_in, rec), self.second.emitted(r_in, rec)) {
+            (None, None) => None,
+            (first, second) => Some(w * first.unwrap_or(Color::ZERO) + (1.0 - w) * second.unwrap_or(Color::ZERO)),
+        }
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let w = mask_value(self.weight.as_ref(), rec.u, rec.v, rec.p);
+        match (self.first.eval(r_in, rec, direction), self.second.eval(r_in, rec, direction)) {
+            (None, None) => None,
+            (first, second) => Some(w * first.unwrap_or(Color::ZERO) + (1.0 - w) * second.unwrap_or(Color::ZERO)),
+        }
+    }
+
+    fn opacity(&self, rec: &HitRecord) -> f64 {
+        let w = mask_value(self.weight.as_ref(), rec.u, rec.v, rec.p);
+        w * self.first.opacity(rec) + (1.0 - w) * self.second.opacity(rec)
+    }
+}
+
+/// 镂空材质，用于贴在 Quad 上的树叶、栅栏。
+/// opacity 为 0 的地方光线原方向直接穿过，为 1 的地方交给 base 材质
+pub struct AlphaCutout {
+    base: Arc<dyn Material>,
+    opacity: Arc<dyn Texture>,
+}
+
+impl AlphaCutout {
+    pub fn new(base: Arc<dyn Material>, opacity: Arc<dyn Texture>) -> AlphaCutout {
+        AlphaCutout { base, opacity }
+    }
+}
+
+impl Material for AlphaCutout {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        if random() < mask_value(self.opacity.as_ref(), rec.u, rec.v, rec.p) {
+            self.base.scatter(r_in, rec)
+        } else {
+            Some(ScatterRecord::specular(Color::ONE, Ray::new_with_time(rec.p, r_in.direction(), r_in.time())))
+        }
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        let opacity = mask_value(self.opacity.as_ref(), rec.u, rec.v, rec.p);
+        self.base.emitted(r_in, rec).map(|emit| opacity * emit)
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let opacity = mask_value(self.opacity.as_ref(), rec.u, rec.v, rec.p);
+        self.base.eval(r_in, rec, direction).map(|f| opacity * f)
+    }
+
+    fn opacity(&self, rec: &HitRecord) -> f64 {
+        mask_value(self.opacity.as_ref(), rec.u, rec.v, rec.p) * self.base.opacity(rec)
+    }
+}
+
+/// 法线贴图：纹理颜色按 rgb * 2 - 1 解码成切线空间法线，替换着色法线后交给 base 材质
+pub struct NormalMapped {
+    base: Arc<dyn Material>,
+    normal_map: Arc<dyn Texture>,
+    strength: f64, // 切线方向分量的缩放，1 为贴图原样
+}
+
+impl NormalMapped {
+    pub fn new(base: Arc<dyn Material>, normal_map: Arc<dyn Texture>, strength: f64) -> NormalMapped {
+        NormalMapped {
+            base,
+            normal_map,
+            strength,
+        }
+    }
+
+    fn shading_record(&self, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+        let (tangent, bitangent) = rec.tangent_frame();
+        let encoded = 2.0 * self.normal_map.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) - Color::ONE;
+        let shading_normal = self.strength * (encoded.x * tangent + encoded.y * bitangent) + encoded.z * rec.unit_normal;
+        with_shading_normal(r_in, rec, shading_normal)
+    }
+}
+
+impl Material for NormalMapped {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        self.base.scatter(r_in, &self.shading_record(r_in, rec))
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        self.base.eval(r_in, &self.shading_record(r_in, rec), direction)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn opacity(&self, rec: &HitRecord) -> f64 {
+        self.base.opacity(rec)
+    }
+}
+
+/// 凹凸贴图：把纹理（取三通道平均）当成沿法线的高度，用有限差分求扰动后的着色法线。
+/// 对 NoiseTexture 这类立体纹理，差分时 p 也会沿 dpdu / dpdv 挪动
+pub struct BumpMapped {
+    base: Arc<dyn Material>,
+    height: Arc<dyn Texture>,
+    strength: f64, // 高度缩放
+}
+
+impl BumpMapped {
+    pub fn new(base: Arc<dyn Material>, height: Arc<dyn Texture>, strength: f64) -> BumpMapped {
+        BumpMapped {
+            base,
+            height,
+            strength,
+        }
+    }
+
+    fn height_at(&self, u: f64, v: f64, p: Point3) -> f64 {
+        self.strength * self.height.value(u, v, p).element_sum() / 3.0
+    }
+
+    fn shading_record(&self, r_in: &Ray, rec: &HitRecord) -> HitRecord {
+        let (dpdu, dpdv) = if rec.dpdu.near_zero() || rec.dpdv.near_zero() {
+            rec.tangent_frame()
+        } else {
+            (rec.dpdu, rec.dpdv)
+        };
+        let delta = constant::BUMP_DELTA;
+        let h = self.height_at(rec.u, rec.v, rec.p);
+        let h_du = self.height_at(rec.u + delta, rec.v, rec.p + delta * dpdu);
+        let h_dv = self.height_at(rec.u, rec.v + delta, rec.p + delta * dpdv);
+
+        // 位移后的曲面 p' = p + h * n，忽略法线本身的变化
+        let displaced_dpdu = dpdu + (h_du - h) / delta * rec.unit_normal;
+        let displaced_dpdv = dpdv + (h_dv - h) / delta * rec.unit_normal;
+        let mut shading_normal = displaced_dpdu.cross(displaced_dpdv);
+        if shading_normal.dot(rec.unit_normal) < 0.0 {
+            shading_normal = -shading_normal;
+        }
+        with_shading_normal(r_in, rec, shading_normal)
+    }
+}
+
+impl Material for BumpMapped {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        self.base.scatter(r_in, &self.shading_record(r_in, rec))
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        self.base.emitted(r_in, rec)
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        self.base.eval(r_in, &self.shading_record(r_in, rec), direction)
+    }
+
+    fn opacity(&self, rec: &HitRecord) -> f64 {
+        self.base.opacity(rec)
+    }
+}
+
+// 复制一份替换了着色法线的 HitRecord。扰动后背对入射光线的法线没有意义，退回几何法线
+fn with_shading_normal(r_in: &Ray, rec: &HitRecord, shading_normal: DVec3) -> HitRecord {
+    let mut shading_rec = rec.clone();
+    if !shading_normal.near_zero() && shading_normal.dot(r_in.direction()) < 0.0 {
+        shading_rec.unit_normal = shading_normal.normalize();
     }
+    shading_rec
+}
+
+// 把纹理颜色当作 [0, 1] 的标量遮罩
+fn mask_value(tex: &dyn Texture, u: f64, v: f64, p: Point3) -> f64 {
+    (tex.value(u, v, p).element_sum() / 3.0).clamp(0.0, 1.0)
 }
 
 pub struct DiffuseLight {
-    tex: Arc<dyn Texture>
+    tex: Arc<dyn Texture>,
+    two_sided: bool,
+    profile: EmissionProfile,
 }
 
 
 impl DiffuseLight {
     pub fn new_from_solid_color(emit: Color) -> DiffuseLight {
+        DiffuseLight::new(Arc::new(SolidColor::new(emit)), true, EmissionProfile::Diffuse)
+    }
+
+    pub fn new_from_texture(tex: Arc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight::new(tex, true, EmissionProfile::Diffuse)
+    }
+
+    /// two_sided 为 false 时只有正面（outward normal 一侧）发光
+    pub fn new(tex: Arc<dyn Texture>, two_sided: bool, profile: EmissionProfile) -> DiffuseLight {
         DiffuseLight {
-            tex: Arc::new(SolidColor::new(emit)),
+            tex,
+            two_sided,
+            profile,
         }
     }
 
-    pub fn new_from_texture(tex: Arc<dyn Texture>) -> DiffuseLight {
-        DiffuseLight { tex }
+    /// 用总功率（瓦）指定光源强度，按发光面积、发光面数和方向分布换算成辐射亮度。
+    /// color 只表示光的颜色，area 是发光物体的表面积，例如 Quad 为 |u × v|
+    pub fn new_from_power(color: Color, watts: f64, area: f64, two_sided: bool, profile: EmissionProfile) -> DiffuseLight {
+        let sides = if two_sided { 2.0 } else { 1.0 };
+        let radiance = watts / (area * sides * profile.projected_solid_angle());
+        DiffuseLight::new(Arc::new(SolidColor::new(color * radiance)), two_sided, profile)
     }
 }
 
 impl Material for DiffuseLight {
-    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<ScatterRecord> {
         None
     }
-    fn emitted(&self, u: f64, v: f64, p: Point3) -> Option<Color> {
-        Some(self.tex.value(u, v, p))
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> Option<Color> {
+        if !self.two_sided && !rec.front_face {
+            return None;
+        }
+        // unit_normal 总是朝向入射光线一侧，和出射方向的夹角即 -r_in 与法线的夹角
+        let cos_theta = (-r_in.direction().normalize()).dot(rec.unit_normal);
+        let falloff = self.profile.falloff(cos_theta);
+        if falloff <= 0.0 {
+            return None;
+        }
+        Some(falloff * self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()))
+    }
+}
+
+impl EmissionProfile {
+    /// 出射方向与法线夹角余弦为 cos_theta 时，辐射亮度相对法线方向的比例
+    pub fn falloff(&self, cos_theta: f64) -> f64 {
+        if cos_theta <= 0.0 {
+            return 0.0;
+        }
+        match *self {
+            EmissionProfile::Diffuse => 1.0,
+            EmissionProfile::CosinePower(exponent) => cos_theta.powf(exponent),
+            EmissionProfile::Spot { cone_angle, falloff_angle } => {
+                let cos_cone = cone_angle.to_radians().cos();
+                let cos_falloff = falloff_angle.min(cone_angle).to_radians().cos();
+                if cos_theta <= cos_cone {
+                    0.0
+                } else if cos_theta >= cos_falloff {
+                    1.0
+                } else {
+                    let t = (cos_theta - cos_cone) / (cos_falloff - cos_cone);
+                    t * t * (3.0 - 2.0 * t)
+                }
+            }
+        }
+    }
+
+    // 半球上 ∫ falloff(cos) * cos dω，Diffuse 时为 π
+    fn projected_solid_angle(&self) -> f64 {
+        let steps = constant::EMISSION_INTEGRATION_STEPS;
+        let mut sum = 0.0;
+        for i in 0..steps {
+            let cos_theta = (i as f64 + 0.5) / steps as f64;
+            sum += self.falloff(cos_theta) * cos_theta;
+        }
+        2.0 * PI * sum / steps as f64
     }
 }
 
@@ -159,9 +671,87 @@ impl Isotropic {
 }
 
 impl Material for Isotropic {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
         let scattered = Ray::new_with_time(rec.p, DVec3::random_unit(), r_in.time());
-        let attenuation = self.tex.value(rec.u, rec.v, rec.p);
-        Some((attenuation, scattered))
+        let attenuation = self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint());
+        Some(ScatterRecord::diffuse(attenuation, scattered))
+    }
+
+    // 各向同性的相函数为 1 / 4π，体积里没有余弦项
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, _direction: DVec3) -> Option<Color> {
+        Some(self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) / (4.0 * PI))
     }
-}
\ No newline at end of file
+}
+
+impl PhaseFunction {
+    /// 入射方向和出射方向夹角余弦为 cos_theta 时的相函数值，在整个球面上积分为 1
+    pub fn evaluate(&self, cos_theta: f64) -> f64 {
+        match *self {
+            PhaseFunction::Isotropic => 1.0 / (4.0 * PI),
+            PhaseFunction::HenyeyGreenstein(g) => henyey_greenstein(g, cos_theta),
+            PhaseFunction::DoubleHenyeyGreenstein { forward, backward, weight } => {
+                weight * henyey_greenstein(forward, cos_theta) + (1.0 - weight) * henyey_greenstein(backward, cos_theta)
+            }
+        }
+    }
+
+    /// 按相函数本身的分布采样出射方向，direction 是入射光线的传播方向
+    pub fn sample(&self, direction: DVec3) -> DVec3 {
+        let g = match *self {
+            PhaseFunction::Isotropic => return DVec3::random_unit(),
+            PhaseFunction::HenyeyGreenstein(g) => g,
+            PhaseFunction::DoubleHenyeyGreenstein { forward, backward, weight } => {
+                if random() < weight { forward } else { backward }
+            }
+        };
+
+        let xi = random();
+        let cos_theta = if g.abs() < constant::NEAR_ZERO_THRESHOLD {
+            1.0 - 2.0 * xi
+        } else {
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+
+        let w = direction.normalize();
+        let (u, v) = w.any_orthonormal_pair();
+        sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * w
+    }
+}
+
+fn henyey_greenstein(g: f64, cos_theta: f64) -> f64 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+/// 各向异性介质的散射，散射方向按相函数采样，所以 attenuation 就是反照率
+pub struct Anisotropic {
+    tex: Arc<dyn Texture>,
+    phase: PhaseFunction,
+}
+
+impl Anisotropic {
+    pub fn new_from_solid_color(albedo: Color, phase: PhaseFunction) -> Anisotropic {
+        Anisotropic {
+            tex: Arc::new(SolidColor::new(albedo)),
+            phase,
+        }
+    }
+
+}
+
+impl Material for Anisotropic {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        let scattered = Ray::new_with_time(rec.p, self.phase.sample(r_in.direction()), r_in.time());
+        let attenuation = self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint());
+        Some(ScatterRecord::diffuse(attenuation, scattered))
+    }
+
+    // 体积里没有余弦项
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: DVec3) -> Option<Color> {
+        let cos_theta = r_in.direction().normalize().dot(direction.normalize());
+        Some(self.tex.value_filtered(rec.u, rec.v, rec.p, rec.uv_footprint()) * self.phase.evaluate(cos_theta))
+    }
+}
@@ -4,45 +4,40 @@ use rand::{Rng, seq::SliceRandom};
 
 use crate::config;
 use crate::constant::PERLIN_POINT_COUNT;
-use crate::enums::NoiseType;
 use crate::point3::Point3;
-use crate::random_number_generator::{get_random_generator, random_range};
+use crate::random_number_generator::get_seeded_generator;
 pub struct Perlin {
-    randfloat: Option<[f64; PERLIN_POINT_COUNT]>,
-    randvec: Option<[DVec3; PERLIN_POINT_COUNT]>,
+    randfloat: [f64; PERLIN_POINT_COUNT],
+    randvec: [DVec3; PERLIN_POINT_COUNT],
     perm_x: [usize; PERLIN_POINT_COUNT],
     perm_y: [usize; PERLIN_POINT_COUNT],
     perm_z: [usize; PERLIN_POINT_COUNT],
 }
 
 impl Perlin {
-    pub fn new() -> Perlin {
-
-        let randfloat: Option<[f64; PERLIN_POINT_COUNT]>;
-        let randvec: Option<[DVec3; PERLIN_POINT_COUNT]>;
-        match config::NOISE_TYPE {
-            NoiseType::HashedRandom | NoiseType::TrilinearInterpolation => {
-                randfloat = Some(get_random_generator().random());
-                randvec = None;
-            }
-            NoiseType::LatticeRandomVectors | NoiseType::Turbulence | NoiseType::TurbulenceMarble => {
-                randfloat = None;
-                randvec = Some(std::array::from_fn(|_| DVec3::new(random_range(-1.0..1.0), random_range(-1.0..1.0), random_range(-1.0..1.0))));
-            }
-        }
+    /// 所有随机表都由 seed 生成，同一个 seed 得到的噪声完全相同
+    pub fn new(seed: u64) -> Perlin {
+        let mut rng = get_seeded_generator(seed);
+        let randfloat = std::array::from_fn(|_| rng.random());
+        let randvec = std::array::from_fn(|_| {
+            DVec3::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0))
+        });
 
-        let perlin_generate_perm = || {
+        let mut perlin_generate_perm = || {
             let mut perm: [usize; PERLIN_POINT_COUNT] = std::array::from_fn(|x| x);
-            perm.shuffle(&mut get_random_generator());
+            perm.shuffle(&mut rng);
             perm
         };
+        let perm_x = perlin_generate_perm();
+        let perm_y = perlin_generate_perm();
+        let perm_z = perlin_generate_perm();
 
         Perlin {
             randfloat,
             randvec,
-            perm_x: perlin_generate_perm(),
-            perm_y: perlin_generate_perm(),
-            perm_z: perlin_generate_perm(),
+            perm_x,
+            perm_y,
+            perm_z,
         }
     }
 
@@ -50,7 +45,7 @@ impl Perlin {
         let i = (4.0 * p.x) as isize & 255;
         let j = (4.0 * p.y) as isize & 255;
         let k = (4.0 * p.z) as isize & 255;
-        self.randfloat.unwrap()[self.perm_x[i as usize] ^ self.perm_y[j as usize] ^ self.perm_z[k as usize]]
+        self.randfloat[self.perm_x[i as usize] ^ self.perm_y[j as usize] ^ self.perm_z[k as usize]]
     }
 
     pub fn trilinear_interpolation_noise(&self, p: Point3) -> f64 {
@@ -72,7 +67,7 @@ impl Perlin {
                     let index = self.perm_x[((i + di) & 255) as usize]
                         ^ self.perm_y[((j + dj) & 255) as usize]
                         ^ self.perm_z[((k + dk) & 255) as usize];
-                    c[di as usize][dj as usize][dk as usize] = self.randfloat.unwrap()[index];
+                    c[di as usize][dj as usize][dk as usize] = self.randfloat[index];
                 }
             }
         }
@@ -112,7 +107,7 @@ impl Perlin {
                     let index = self.perm_x[((i + di) & 255) as usize]
                         ^ self.perm_y[((j + dj) & 255) as usize]
                         ^ self.perm_z[((k + dk) & 255) as usize];
-                    c[di as usize][dj as usize][dk as usize] = self.randvec.unwrap()[index];
+                    c[di as usize][dj as usize][dk as usize] = self.randvec[index];
                 }
             }
         }
@@ -142,15 +137,107 @@ impl Perlin {
         accum
     }
 
-    pub fn turb(&self, p: Point3, depth: usize) -> f64 {
+    /// 分形叠加：每一层频率乘以 lacunarity，振幅乘以 gain
+    pub fn fbm(&self, p: Point3, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        Self::fractal(p, octaves, lacunarity, gain, |q| self.lattice_random_vectors_noise(q))
+    }
+
+    pub fn simplex_fbm(&self, p: Point3, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        Self::fractal(p, octaves, lacunarity, gain, |q| self.simplex_noise(q))
+    }
+
+    /// 用三个错开的 fBm 组成位移向量扭曲 p，再在扭曲后的位置求 fBm，得到云雾一样卷曲的图案
+    pub fn domain_warped_fbm(&self, p: Point3, octaves: usize, lacunarity: f64, gain: f64, strength: f64) -> f64 {
+        let warp = DVec3::new(
+            self.fbm(p, octaves, lacunarity, gain),
+            self.fbm(p + DVec3::new(5.2, 1.3, 2.8), octaves, lacunarity, gain),
+            self.fbm(p + DVec3::new(1.7, 9.2, 4.1), octaves, lacunarity, gain),
+        );
+        self.fbm(p + strength * warp, octaves, lacunarity, gain)
+    }
+
+    fn fractal(p: Point3, octaves: usize, lacunarity: f64, gain: f64, basis: impl Fn(Point3) -> f64) -> f64 {
         let mut accum = 0.0;
         let mut temp_p = p;
         let mut weight = 1.0;
-        for _ in 0..depth {
-            accum += weight * self.lattice_random_vectors_noise(temp_p);
-            weight *= 0.5;
-            temp_p *= 2.0;
+        for _ in 0..octaves {
+            accum += weight * basis(temp_p);
+            weight *= gain;
+            temp_p *= lacunarity;
         }
-        accum.abs()
+        accum
+    }
+
+    fn hash(&self, i: isize, j: isize, k: isize) -> usize {
+        self.perm_x[(i & 255) as usize] ^ self.perm_y[(j & 255) as usize] ^ self.perm_z[(k & 255) as usize]
+    }
+
+    /// 三维 Simplex 噪声，把空间切成四面体，只累加所在四面体 4 个顶点的贡献，值域约为 [-1, 1]
+    pub fn simplex_noise(&self, p: Point3) -> f64 {
+        const SKEW: f64 = 1.0 / 3.0;
+        const UNSKEW: f64 = 1.0 / 6.0;
+
+        // 斜切到立方体网格上找到所在的格子
+        let skewed = (p + DVec3::splat((p.x + p.y + p.z) * SKEW)).floor();
+        let cell_origin = skewed - DVec3::splat((skewed.x + skewed.y + skewed.z) * UNSKEW);
+        let d0 = p - cell_origin;
+
+        // 按各分量大小决定沿哪条路径走到对角顶点
+        let (offset1, offset2) = if d0.x >= d0.y {
+            if d0.y >= d0.z {
+                (DVec3::X, DVec3::new(1.0, 1.0, 0.0))
+            } else if d0.x >= d0.z {
+                (DVec3::X, DVec3::new(1.0, 0.0, 1.0))
+            } else {
+                (DVec3::Z, DVec3::new(1.0, 0.0, 1.0))
+            }
+        } else if d0.y < d0.z {
+            (DVec3::Z, DVec3::new(0.0, 1.0, 1.0))
+        } else if d0.x < d0.z {
+            (DVec3::Y, DVec3::new(0.0, 1.0, 1.0))
+        } else {
+            (DVec3::Y, DVec3::new(1.0, 1.0, 0.0))
+        };
+
+        let corners = [DVec3::ZERO, offset1, offset2, DVec3::ONE];
+        let mut accum = 0.0;
+        for (n, corner) in corners.iter().enumerate() {
+            let d = d0 - *corner + DVec3::splat(n as f64 * UNSKEW);
+            let falloff = 0.6 - d.length_squared();
+            if falloff > 0.0 {
+                let lattice = skewed + *corner;
+                let gradient = self.randvec[self.hash(lattice.x as isize, lattice.y as isize, lattice.z as isize)].normalize_or_zero();
+                accum += falloff.powi(4) * gradient.dot(d);
+            }
+        }
+        32.0 * accum
+    }
+
+    /// Worley 噪声，每个格子里有一个随机特征点，返回到最近和第二近特征点的距离 (F1, F2)
+    pub fn worley_noise(&self, p: Point3) -> (f64, f64) {
+        let cell = p.floor();
+        let (mut f1, mut f2) = (f64::INFINITY, f64::INFINITY);
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let neighbor = cell + DVec3::new(di as f64, dj as f64, dk as f64);
+                    let index = self.hash(neighbor.x as isize, neighbor.y as isize, neighbor.z as isize);
+                    // randvec 的分量在 [-1, 1)，映射到格子内部
+                    let feature = neighbor + 0.5 * (self.randvec[index] + DVec3::ONE);
+                    let distance = (feature - p).length();
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+        (f1, f2)
+    }
+
+    pub fn turb(&self, p: Point3, octaves: usize, lacunarity: f64, gain: f64) -> f64 {
+        self.fbm(p, octaves, lacunarity, gain).abs()
     }
 }
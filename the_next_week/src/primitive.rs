@@ -0,0 +1,506 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::point3::Point3;
+use crate::ray::Ray;
+
+/// 以 axis 为 y 轴的局部坐标系，回转体都在这里求交，x、z 轴分别是 u、v
+struct LocalFrame {
+    origin: Point3,
+    u: DVec3,
+    axis: DVec3,
+    v: DVec3,
+}
+
+impl LocalFrame {
+    /// axis 长度为 0 时没有方向，随便取 y 轴，退化的情况由各个图元自己处理
+    fn new(origin: Point3, axis: DVec3) -> LocalFrame {
+        let axis = axis.try_normalize().unwrap_or(DVec3::Y);
+        // (v, u, axis) 是右手系，所以 (u, axis, v) 也是
+        let (v, u) = axis.any_orthonormal_pair();
+        LocalFrame { origin, u, axis, v }
+    }
+
+    fn vector_to_local(&self, d: DVec3) -> DVec3 {
+        DVec3::new(d.dot(self.u), d.dot(self.axis), d.dot(self.v))
+    }
+
+    fn vector_to_world(&self, d: DVec3) -> DVec3 {
+        d.x * self.u + d.y * self.axis + d.z * self.v
+    }
+
+    fn ray_to_local(&self, r: &Ray) -> (Point3, DVec3) {
+        (self.vector_to_local(r.origin() - self.origin), self.vector_to_local(r.direction()))
+    }
+
+    /// 圆心在 center、法线是 axis、半径为 radius 的圆盘的包围盒
+    fn disk_bounding_box(&self, center: Point3, radius: f64) -> Aabb {
+        let extent = radius * (DVec3::ONE - self.axis * self.axis).max(DVec3::ZERO).map(f64::sqrt);
+        Aabb::new_from_two_points(center - extent, center + extent)
+    }
+}
+
+/// 绕 y 轴一周的角度换算成 [0, 1) 的纹理坐标，以及 p 对它的偏导（局部坐标）
+fn azimuth_uv(p: Point3) -> (f64, DVec3) {
+    let phi = p.z.atan2(p.x);
+    let u = if phi < 0.0 { phi / (2.0 * PI) + 1.0 } else { phi / (2.0 * PI) };
+    (u, 2.0 * PI * DVec3::new(-p.z, 0.0, p.x))
+}
+
+/// 光线和球心在 y 轴上 center_y 处的球的两个交点，没有交点返回 None
+fn hit_axis_sphere(o: Point3, d: DVec3, center_y: f64, radius: f64) -> Option<(f64, f64)> {
+    let oc = o - DVec3::new(0.0, center_y, 0.0);
+    let a = d.length_squared();
+    let half_b = oc.dot(d);
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrtd = discriminant.sqrt();
+    Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+}
+
+/// 圆台：底面 y = 0 半径 bottom_radius，顶面 y = height 半径 top_radius。
+/// 圆柱和圆锥都是它的特例，侧面满足 x^2 + z^2 = (bottom_radius + slope * y)^2
+struct Frustum {
+    frame: LocalFrame,
+    height: f64,
+    bottom_radius: f64,
+    top_radius: f64,
+    slope: f64,
+    capped: bool,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Frustum {
+    /// axis 长度为 0 时高度为 0，什么也打不中
+    fn new(base: Point3, axis: DVec3, bottom_radius: f64, top_radius: f64, capped: bool, mat: Arc<dyn Material>) -> Frustum {
+        let frame = LocalFrame::new(base, axis);
+        let height = axis.length();
+        let (bottom_radius, top_radius) = (bottom_radius.max(0.0), top_radius.max(0.0));
+        let degenerate = !(height > 0.0 && height.is_finite());
+        let bbox = if degenerate {
+            Aabb::EMPTY
+        } else {
+            Aabb::new_from_merged(
+                frame.disk_bounding_box(base, bottom_radius),
+                frame.disk_bounding_box(base + axis, top_radius),
+            )
+        };
+        Frustum {
+            frame,
+            height: if degenerate { 0.0 } else { height },
+            bottom_radius,
+            top_radius,
+            slope: if degenerate { 0.0 } else { (top_radius - bottom_radius) / height },
+            capped,
+            mat,
+            bbox,
+        }
+    }
+
+    fn radius_at(&self, y: f64) -> f64 {
+        self.bottom_radius + self.slope * y
+    }
+
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.height == 0.0 {
+            return None;
+        }
+        let (o, d) = self.frame.ray_to_local(r);
+
+        // 先找侧面和两个底面里最近的交点，最后只构造一次 HitRecord
+        let mut closest: Option<(f64, bool)> = None; // (t, 是否在侧面)
+        let mut consider = |t: f64, on_side: bool| {
+            if ray_t.surrounds(t) && closest.is_none_or(|(closest_t, _)| t < closest_t) {
+                closest = Some((t, on_side));
+            }
+        };
+
+        let radius_o = self.radius_at(o.y);
+        let a = d.x * d.x + d.z * d.z - self.slope * self.slope * d.y * d.y;
+        let half_b = o.x * d.x + o.z * d.z - radius_o * self.slope * d.y;
+        let c = o.x * o.x + o.z * o.z - radius_o * radius_o;
+        let discriminant = half_b * half_b - a * c;
+        if a.abs() > 0.0 && discriminant >= 0.0 {
+            let sqrtd = discriminant.sqrt();
+            for t in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+                let y = o.y + t * d.y;
+                if (0.0..=self.height).contains(&y) {
+                    consider(t, true);
+                }
+            }
+        }
+
+        if self.capped && d.y != 0.0 {
+            for (y, radius) in [(0.0, self.bottom_radius), (self.height, self.top_radius)] {
+                let t = (y - o.y) / d.y;
+                let (x, z) = (o.x + t * d.x, o.z + t * d.z);
+                if x * x + z * z <= radius * radius {
+                    consider(t, false);
+                }
+            }
+        }
+
+        let (t, on_side) = closest?;
+        let p = o + t * d;
+        let (outward_normal, uv, dpdu, dpdv) = if on_side {
+            let (u, dpdu) = azimuth_uv(p);
+            let radial = DVec3::new(p.x, 0.0, p.z).normalize_or_zero();
+            let normal = (radial - self.slope * DVec3::Y).normalize();
+            let dpdv = (self.top_radius - self.bottom_radius) * radial + self.height * DVec3::Y;
+            (normal, (u, p.y / self.height), dpdu, dpdv)
+        } else {
+            // 底面按圆盘贴图，从 -x 到 +x 是 u，从 -z 到 +z 是 v
+            let top = p.y > 0.5 * self.height;
+            let radius = if top { self.top_radius } else { self.bottom_radius };
+            let normal = if top { DVec3::Y } else { DVec3::NEG_Y };
+            let uv = (p.x / radius / 2.0 + 0.5, p.z / radius / 2.0 + 0.5);
+            (normal, uv, DVec3::new(2.0 * radius, 0.0, 0.0), DVec3::new(0.0, 0.0, 2.0 * radius))
+        };
+
+        let rec = HitRecord::with_hit_data(t, r.at(t), uv, r, self.frame.vector_to_world(outward_normal), self.mat.clone())
+            .with_tangents(self.frame.vector_to_world(dpdu), self.frame.vector_to_world(dpdv));
+        Some(rec)
+    }
+}
+
+/// 底面圆心在 base、沿 axis 方向延伸 axis.length() 的圆柱。capped 为 false 时是两端开口的管子
+pub struct Cylinder {
+    body: Frustum,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, axis: DVec3, radius: f64, capped: bool, mat: Arc<dyn Material>) -> Cylinder {
+        Cylinder {
+            body: Frustum::new(base, axis, radius, radius, capped, mat),
+        }
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.body.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.body.bbox
+    }
+}
+
+/// 圆锥或圆台，底面圆心在 base，顶面圆心在 base + axis。top_radius 为 0 时是尖顶的圆锥
+pub struct Cone {
+    body: Frustum,
+}
+
+impl Cone {
+    pub fn new(base: Point3, axis: DVec3, bottom_radius: f64, top_radius: f64, capped: bool, mat: Arc<dyn Material>) -> Cone {
+        Cone {
+            body: Frustum::new(base, axis, bottom_radius, top_radius, capped, mat),
+        }
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.body.hit(r, ray_t)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.body.bbox
+    }
+}
+
+/// 胶囊：线段 a-b 上所有点周围 radius 范围内的区域，也就是圆柱两端各接一个半球。
+/// 纹理坐标 v 沿轮廓线的弧长从 a 端的极点走到 b 端的极点。a、b 重合时就是球
+pub struct Capsule {
+    frame: LocalFrame,
+    length: f64,
+    radius: f64,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Capsule {
+    pub fn new(a: Point3, b: Point3, radius: f64, mat: Arc<dyn Material>) -> Capsule {
+        let radius = radius.max(0.0);
+        let rvec = DVec3::splat(radius);
+        Capsule {
+            frame: LocalFrame::new(a, b - a),
+            length: (b - a).length(),
+            radius,
+            mat,
+            bbox: Aabb::new_from_merged(
+                Aabb::new_from_two_points(a - rvec, a + rvec),
+                Aabb::new_from_two_points(b - rvec, b + rvec),
+            ),
+        }
+    }
+}
+
+impl Hittable for Capsule {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let (o, d) = self.frame.ray_to_local(r);
+
+        let mut closest: Option<f64> = None;
+        let mut consider = |t: f64| {
+            if ray_t.surrounds(t) && closest.is_none_or(|closest_t| t < closest_t) {
+                closest = Some(t);
+            }
+        };
+
+        // 侧面是无限长圆柱落在 [0, length] 里的部分，两端的球只保留外侧的半球
+        let a = d.x * d.x + d.z * d.z;
+        let half_b = o.x * d.x + o.z * d.z;
+        let c = o.x * o.x + o.z * o.z - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if a > 0.0 && discriminant >= 0.0 {
+            let sqrtd = discriminant.sqrt();
+            for t in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+                if (0.0..=self.length).contains(&(o.y + t * d.y)) {
+                    consider(t);
+                }
+            }
+        }
+        for (center_y, outside) in [(0.0, -1.0), (self.length, 1.0)] {
+            if let Some((t0, t1)) = hit_axis_sphere(o, d, center_y, self.radius) {
+                for t in [t0, t1] {
+                    if (o.y + t * d.y - center_y) * outside >= 0.0 {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        let t = closest?;
+        let p = o + t * d;
+        let center = DVec3::new(0.0, p.y.clamp(0.0, self.length), 0.0);
+        let outward_normal = (p - center) / self.radius;
+
+        // 轮廓线总长是两个四分之一圆弧加上中间的直线段
+        let profile_length = PI * self.radius + self.length;
+        let arc = if p.y < 0.0 {
+            self.radius * (-outward_normal.y).clamp(-1.0, 1.0).acos()
+        } else if p.y > self.length {
+            0.5 * PI * self.radius + self.length + self.radius * outward_normal.y.clamp(-1.0, 1.0).asin()
+        } else {
+            0.5 * PI * self.radius + p.y
+        };
+        let (u, dpdu) = azimuth_uv(p);
+        // 沿轮廓线往 b 端走的单位切线
+        let radial = DVec3::new(outward_normal.x, 0.0, outward_normal.z).normalize_or_zero();
+        let profile_tangent = -outward_normal.y * radial + radial.dot(outward_normal) * DVec3::Y;
+
+        let rec = HitRecord::with_hit_data(
+            t,
+            r.at(t),
+            (u, arc / profile_length),
+            r,
+            self.frame.vector_to_world(outward_normal),
+            self.mat.clone(),
+        )
+        .with_tangents(self.frame.vector_to_world(dpdu), self.frame.vector_to_world(profile_length * profile_tangent));
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+/// 圆环：管子的中心线是绕 axis、半径 major_radius 的圆，管子半径 minor_radius。
+/// 求交要解一元四次方程。纹理坐标 u 绕 axis 一周，v 绕管子一周，从最外侧开始往 axis 正方向转
+pub struct Torus {
+    frame: Option<LocalFrame>, // axis 长度为 0 时圆环没有朝向，什么也打不中
+    major_radius: f64,
+    minor_radius: f64,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Torus {
+    pub fn new(center: Point3, axis: DVec3, major_radius: f64, minor_radius: f64, mat: Arc<dyn Material>) -> Torus {
+        if axis.try_normalize().is_none() {
+            return Torus {
+                frame: None,
+                major_radius,
+                minor_radius,
+                mat,
+                bbox: Aabb::EMPTY,
+            };
+        }
+        let frame = LocalFrame::new(center, axis);
+        // 中心线圆的包围盒每个方向再加上管子半径，是紧的
+        let extent = major_radius * (DVec3::ONE - frame.axis * frame.axis).max(DVec3::ZERO).map(f64::sqrt) + DVec3::splat(minor_radius);
+        Torus {
+            frame: Some(frame),
+            major_radius,
+            minor_radius,
+            mat,
+            bbox: Aabb::new_from_two_points(center - extent, center + extent),
+        }
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let frame = self.frame.as_ref()?;
+        let (o, d) = frame.ray_to_local(r);
+        let (major, minor) = (self.major_radius, self.minor_radius);
+
+        // 光线离圆环很远时系数会很大，先把起点挪到离中心最近的位置，解出来再挪回去
+        let dd = d.length_squared();
+        let shift = -o.dot(d) / dd;
+        let o = o + shift * d;
+
+        // (|p|^2 + R^2 - r^2)^2 = 4 R^2 (p.x^2 + p.z^2)，p = o + t d
+        let od = o.dot(d);
+        let g = o.length_squared() + major * major - minor * minor;
+        let four_r2 = 4.0 * major * major;
+        let coefficients = [
+            g * g - four_r2 * (o.x * o.x + o.z * o.z),
+            4.0 * od * g - 2.0 * four_r2 * (o.x * d.x + o.z * d.z),
+            4.0 * od * od + 2.0 * dd * g - four_r2 * (d.x * d.x + d.z * d.z),
+            4.0 * dd * od,
+            dd * dd,
+        ];
+
+        let t = solve_quartic(coefficients)
+            .into_iter()
+            .map(|s| s + shift)
+            .filter(|&t| ray_t.surrounds(t))
+            .min_by(f64::total_cmp)?;
+
+        let p = o + (t - shift) * d;
+        let radial = DVec3::new(p.x, 0.0, p.z).normalize_or_zero();
+        let outward_normal = (p - major * radial).normalize();
+        let (u, dpdu) = azimuth_uv(p);
+        let theta = outward_normal.y.atan2(outward_normal.dot(radial));
+        let v = if theta < 0.0 { theta / (2.0 * PI) + 1.0 } else { theta / (2.0 * PI) };
+        let dpdv = 2.0 * PI * minor * (-theta.sin() * radial + theta.cos() * DVec3::Y);
+
+        let rec = HitRecord::with_hit_data(t, r.at(t), (u, v), r, frame.vector_to_world(outward_normal), self.mat.clone())
+            .with_tangents(frame.vector_to_world(dpdu), frame.vector_to_world(dpdv));
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+const EQUATION_EPSILON: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EQUATION_EPSILON
+}
+
+/// c[0] + c[1] x + c[2] x^2 = 0 的实根
+fn solve_quadratic(c: [f64; 3]) -> Vec<f64> {
+    let p = c[1] / (2.0 * c[2]);
+    let q = c[0] / c[2];
+    let discriminant = p * p - q;
+    if is_zero(discriminant) {
+        vec![-p]
+    } else if discriminant < 0.0 {
+        Vec::new()
+    } else {
+        let sqrtd = discriminant.sqrt();
+        vec![sqrtd - p, -sqrtd - p]
+    }
+}
+
+/// c[0] + c[1] x + c[2] x^2 + c[3] x^3 = 0 的实根，卡尔达诺公式
+fn solve_cubic(c: [f64; 4]) -> Vec<f64> {
+    // 化成 x^3 + A x^2 + B x + C = 0，再代换 x = y - A/3 消去二次项
+    let (a, b, cc) = (c[2] / c[3], c[1] / c[3], c[0] / c[3]);
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + cc) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let mut roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // 三个不同实根，用三角函数形式
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![t * phi.cos(), -t * (phi + PI / 3.0).cos(), -t * (phi - PI / 3.0).cos()]
+    } else {
+        let sqrtd = discriminant.sqrt();
+        vec![(sqrtd - q).cbrt() - (sqrtd + q).cbrt()]
+    };
+
+    for root in &mut roots {
+        *root -= a / 3.0;
+    }
+    roots
+}
+
+/// c[0] + c[1] x + ... + c[4] x^4 = 0 的实根，费拉里法。
+/// 闭式解在根很接近时误差较大，最后用牛顿迭代在原方程上修正几次
+fn solve_quartic(c: [f64; 5]) -> Vec<f64> {
+    // 化成 x^4 + A x^3 + B x^2 + C x + D = 0，再代换 x = y - A/4 消去三次项：y^4 + p y^2 + q y + r = 0
+    let (a, b, cc, d) = (c[3] / c[4], c[2] / c[4], c[1] / c[4], c[0] / c[4]);
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + cc;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * cc / 4.0 + d;
+
+    let mut roots = if is_zero(r) {
+        // y (y^3 + p y + q) = 0
+        let mut roots = solve_cubic([q, p, 0.0, 1.0]);
+        roots.push(0.0);
+        roots
+    } else {
+        // 预解三次方程的任意一个实根 z 把四次式拆成两个二次式
+        let z = solve_cubic([r * p / 2.0 - q * q / 8.0, -r, -p / 2.0, 1.0])[0];
+        let mut u = z * z - r;
+        let mut v = 2.0 * z - p;
+        if is_zero(u) {
+            u = 0.0;
+        } else if u > 0.0 {
+            u = u.sqrt();
+        } else {
+            return Vec::new();
+        }
+        if is_zero(v) {
+            v = 0.0;
+        } else if v > 0.0 {
+            v = v.sqrt();
+        } else {
+            return Vec::new();
+        }
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = solve_quadratic([z - u, v, 1.0]);
+        roots.extend(solve_quadratic([z + u, -v, 1.0]));
+        roots
+    };
+
+    let evaluate = |x: f64| ((((c[4] * x + c[3]) * x + c[2]) * x + c[1]) * x + c[0], ((4.0 * c[4] * x + 3.0 * c[3]) * x + 2.0 * c[2]) * x + c[1]);
+    for root in &mut roots {
+        *root -= a / 4.0;
+        for _ in 0..2 {
+            let (f, df) = evaluate(*root);
+            if df != 0.0 {
+                *root -= f / df;
+            }
+        }
+    }
+    roots
+}
@@ -1,9 +1,11 @@
 use std::ops::Range;
 
-use rand::rngs::ThreadRng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 
-pub fn get_random_generator() -> ThreadRng {
-    rand::rng()
+/// 固定种子的随机数生成器，同一个种子每次运行得到的序列都相同
+pub fn get_seeded_generator(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
 }
 
 /// Generates a random number in the range [0, 1)
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use glam::{DVec2, DVec3};
+
+use crate::aabb::Aabb;
+use crate::constant;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::point3::Point3;
+use crate::ray::Ray;
+
+/// 有符号距离函数：物体外面为正、里面为负，绝对值不超过到表面的真实距离。
+/// 分形之类只能给出距离下界的估计也可以，只是要多走几步
+pub type DistanceFn = Arc<dyn Fn(Point3) -> f64 + Send + Sync>;
+
+pub fn sphere(center: Point3, radius: f64) -> DistanceFn {
+    Arc::new(move |p| (p - center).length() - radius)
+}
+
+/// half_extents 是长方体每个方向的半边长
+pub fn cuboid(center: Point3, half_extents: DVec3) -> DistanceFn {
+    Arc::new(move |p| box_distance(p - center, half_extents))
+}
+
+/// 棱角倒成半径为 radius 的圆角，外形尺寸不变
+pub fn round_box(center: Point3, half_extents: DVec3, radius: f64) -> DistanceFn {
+    Arc::new(move |p| box_distance(p - center, half_extents - DVec3::splat(radius)) - radius)
+}
+
+/// 中心线是 y = center.y 平面上半径 major_radius 的圆，管子半径 minor_radius
+pub fn torus(center: Point3, major_radius: f64, minor_radius: f64) -> DistanceFn {
+    Arc::new(move |p| {
+        let p = p - center;
+        DVec2::new(DVec2::new(p.x, p.z).length() - major_radius, p.y).length() - minor_radius
+    })
+}
+
+/// 多项式平滑的并集，k 是过渡区域的宽度，k 趋于 0 时退化为普通的并集
+pub fn smooth_union(a: DistanceFn, b: DistanceFn, k: f64) -> DistanceFn {
+    Arc::new(move |p| {
+        let (da, db) = (a(p), b(p));
+        let h = (0.5 + 0.5 * (db - da) / k).clamp(0.0, 1.0);
+        db + (da - db) * h - k * h * (1.0 - h)
+    })
+}
+
+/// 从 a 里平滑地挖掉 b
+pub fn smooth_subtraction(a: DistanceFn, b: DistanceFn, k: f64) -> DistanceFn {
+    Arc::new(move |p| {
+        let (da, db) = (a(p), b(p));
+        let h = (0.5 - 0.5 * (da + db) / k).clamp(0.0, 1.0);
+        da + (-db - da) * h + k * h * (1.0 - h)
+    })
+}
+
+/// power 次的 Mandelbulb 分形，原始大小约为半径 1.2 的球，scale 倍放大后放在 center。
+/// 用逃逸时的导数估计距离，iterations 越多细节越多
+pub fn mandelbulb(center: Point3, scale: f64, power: f64, iterations: usize) -> DistanceFn {
+    Arc::new(move |p| {
+        let c = (p - center) / scale;
+        let mut z = c;
+        let mut dr = 1.0;
+        let mut r = z.length();
+        for _ in 0..iterations {
+            if r > 2.0 {
+                break;
+            }
+            // 球坐标下半径取 power 次方、两个角度乘以 power
+            let theta = (z.z / r).clamp(-1.0, 1.0).acos() * power;
+            let phi = z.y.atan2(z.x) * power;
+            dr = r.powf(power - 1.0) * power * dr + 1.0;
+            z = r.powf(power) * DVec3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos()) + c;
+            r = z.length();
+        }
+        if r <= 0.0 {
+            return 0.0;
+        }
+        0.5 * r.ln() * r / dr * scale
+    })
+}
+
+fn box_distance(p: DVec3, half_extents: DVec3) -> f64 {
+    let q = p.abs() - half_extents;
+    q.max(DVec3::ZERO).length() + q.max_element().min(0.0)
+}
+
+/// 用 sphere tracing 求交的距离场物体：每一步都沿光线前进当前点到表面的距离，直到足够接近表面。
+/// 距离函数本身不知道物体有多大，bounds 要由调用方给出，光线先和它求交再开始走
+pub struct Sdf {
+    distance: DistanceFn,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Sdf {
+    pub fn new(distance: DistanceFn, bounds: Aabb, mat: Arc<dyn Material>) -> Sdf {
+        Sdf { distance, mat, bbox: bounds }
+    }
+
+    /// 距离场的梯度方向，四面体上的四个点做有限差分
+    fn normal(&self, p: Point3) -> DVec3 {
+        let h = constant::SDF_NORMAL_EPSILON;
+        let offsets = [DVec3::new(1.0, -1.0, -1.0), DVec3::new(-1.0, -1.0, 1.0), DVec3::new(-1.0, 1.0, -1.0), DVec3::new(1.0, 1.0, 1.0)];
+        offsets
+            .iter()
+            .map(|&k| k * (self.distance)(p + h * k))
+            .sum::<DVec3>()
+            .normalize_or_zero()
+    }
+}
+
+impl Hittable for Sdf {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let inside_box = self.bbox.hit_interval(r, ray_t)?;
+        let ray_length = r.direction().length();
+        let epsilon = constant::SDF_HIT_EPSILON;
+
+        // 折射进物体内部的光线要朝着距离变大的方向找表面，所以按起点在哪一侧给距离带上符号。
+        // 从包围盒外面射进来的光线起点一定在物体外面；起点在包围盒里并且贴着表面时
+        // （刚从这个物体上反射或折射出来）看光线朝哪边走，并且先离开表面附近再开始判断相交
+        let mut t = inside_box.min;
+        let start_distance = (self.distance)(r.at(t));
+        let from_outside_box = inside_box.min > ray_t.min;
+        let outside = if from_outside_box {
+            true
+        } else if start_distance.abs() < epsilon {
+            self.normal(r.at(t)).dot(r.direction()) > 0.0
+        } else {
+            start_distance > 0.0
+        };
+        let sign = if outside { 1.0 } else { -1.0 };
+        let mut armed = from_outside_box || start_distance.abs() >= epsilon;
+
+        for _ in 0..constant::SDF_MAX_STEPS {
+            let distance = sign * (self.distance)(r.at(t));
+            if armed && distance < epsilon {
+                let p = r.at(t);
+                let mut outward_normal = self.normal(p);
+                if outward_normal == DVec3::ZERO {
+                    outward_normal = -r.direction().normalize();
+                }
+                return Some(HitRecord::with_hit_data(t, p, (0.0, 0.0), r, outward_normal, self.mat.clone()));
+            }
+            armed |= distance >= epsilon;
+            t += distance.max(epsilon) / ray_length;
+            if t >= inside_box.max {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
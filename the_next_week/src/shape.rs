@@ -40,12 +40,20 @@ pub trait Shape: Hittable {
         let alpha = self.get_w().dot(planar_hip_point_vector.cross(self.get_v()));
         let beta = self.get_w().dot(self.get_u().cross(planar_hip_point_vector));
 
-        self.alpha_beta_hit_uv(alpha, beta).map(|uv| HitRecord::with_hit_data(t, intersection, uv, r, self.get_normal(), self.get_mat_clone()))
+        let (dpdu, dpdv) = self.uv_tangents();
+        self.alpha_beta_hit_uv(alpha, beta).map(|uv| {
+            HitRecord::with_hit_data(t, intersection, uv, r, self.get_normal(), self.get_mat_clone()).with_tangents(dpdu, dpdv)
+        })
     }
 
     /// 输入以 self.u, self.v 基向量为坐标轴的坐标，输出纹理坐标
     fn alpha_beta_hit_uv(&self, alpha: f64, beta: f64) -> Option<(f64, f64)>;
 
+    /// 交点对纹理坐标的偏导 (dp/du, dp/dv)，默认纹理坐标就是 alpha, beta
+    fn uv_tangents(&self) -> (DVec3, DVec3) {
+        (self.get_u(), self.get_v())
+    }
+
 }
 
 
@@ -149,6 +157,105 @@ impl Shape for Quad {
 }
 
 
+/// 四个角不必共面的双线性曲面片，p(u, v) = lerp(lerp(p00, p10, u), lerp(p01, p11, u), v)。
+/// 四点共面并且是平行四边形时和 Quad 一样。求交按 Reshetov 的方法先解 u 的二次方程，再求 t 和 v
+pub struct BilinearPatch {
+    p00: Point3,
+    p10: Point3,
+    p01: Point3,
+    p11: Point3,
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl BilinearPatch {
+    pub fn new(p00: Point3, p10: Point3, p01: Point3, p11: Point3, mat: Arc<dyn Material>) -> BilinearPatch {
+        BilinearPatch {
+            p00,
+            p10,
+            p01,
+            p11,
+            mat,
+            bbox: Aabb::new_from_points_vec(vec![p00, p10, p01, p11]), // 曲面片在四个角的凸包内
+        }
+    }
+
+    /// (dp/du, dp/dv)
+    fn tangents(&self, u: f64, v: f64) -> (DVec3, DVec3) {
+        let dpdu = (self.p10 - self.p00).lerp(self.p11 - self.p01, v);
+        let dpdv = (self.p01 - self.p00).lerp(self.p11 - self.p10, u);
+        (dpdu, dpdv)
+    }
+}
+
+impl Hittable for BilinearPatch {
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let d = r.direction();
+        let q00 = self.p00 - r.origin();
+        let q10 = self.p10 - r.origin();
+        let q01 = self.p01 - r.origin();
+        let q11 = self.p11 - r.origin();
+        let e10 = q10 - q00;
+        let e11 = q11 - q10;
+        let e00 = q01 - q00;
+
+        // 光线和 u 处的 v 方向直线共面的条件是 c * u^2 + b * u + a = 0
+        let qn = e10.cross(q01 - q11);
+        let a = q00.cross(d).dot(e00);
+        let c = qn.dot(d);
+        let b = q10.cross(d).dot(e11) - a - c;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let roots = if c == 0.0 {
+            if b == 0.0 {
+                return None;
+            }
+            [-a / b, -1.0]
+        } else {
+            // 避免两个相近的数相减
+            let q = 0.5 * (-b - discriminant.sqrt().copysign(b));
+            if q == 0.0 { [0.0, -b / c] } else { [q / c, a / q] }
+        };
+
+        let mut closest: Option<(f64, f64, f64)> = None;
+        for u in roots {
+            if !(0.0..=1.0).contains(&u) {
+                continue;
+            }
+            // 光线 t * d 和直线 pa + v * pb 的交点
+            let pa = q00.lerp(q10, u);
+            let pb = e00.lerp(e11, u);
+            let n = d.cross(pb);
+            let det = n.length_squared();
+            if det == 0.0 {
+                continue;
+            }
+            let n = n.cross(pa);
+            let t = n.dot(pb) / det;
+            let v = n.dot(d) / det;
+            if (0.0..=1.0).contains(&v) && ray_t.surrounds(t) && closest.is_none_or(|(closest_t, _, _)| t < closest_t) {
+                closest = Some((t, u, v));
+            }
+        }
+
+        let (t, u, v) = closest?;
+        let (dpdu, dpdv) = self.tangents(u, v);
+        // 退化成三角形的角上 dpdu 或 dpdv 为 0，用两条对角线的叉积代替
+        let outward_normal = dpdu.cross(dpdv).try_normalize().unwrap_or_else(|| {
+            (self.p11 - self.p00).cross(self.p01 - self.p10).normalize()
+        });
+        Some(HitRecord::with_hit_data(t, r.at(t), (u, v), r, outward_normal, self.mat.clone()).with_tangents(dpdu, dpdv))
+    }
+}
+
+
 pub struct Tri {
     Q: Point3,
     u: DVec3,
@@ -229,6 +336,30 @@ impl Shape for Tri {
     }
 }
 
+/// Möller–Trumbore，返回 (t, beta, gamma)，beta、gamma 是 b、c 的重心坐标。
+/// 顶点带法线等属性、命中后要插值的三角形（细分曲面、高度场）共用这个测试
+pub(crate) fn intersect_triangle(r: &Ray, ray_t: Interval, [a, b, c]: [Point3; 3]) -> Option<(f64, f64, f64)> {
+    let (edge1, edge2) = (b - a, c - a);
+    let pvec = r.direction().cross(edge2);
+    let det = edge1.dot(pvec);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let tvec = r.origin() - a;
+    let beta = tvec.dot(pvec) * inv_det;
+    if !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+    let qvec = tvec.cross(edge1);
+    let gamma = r.direction().dot(qvec) * inv_det;
+    if gamma < 0.0 || beta + gamma > 1.0 {
+        return None;
+    }
+    let t = edge2.dot(qvec) * inv_det;
+    ray_t.surrounds(t).then_some((t, beta, gamma))
+}
+
 
 pub struct Ellipse {
     center: Point3,
@@ -264,6 +395,12 @@ impl Ellipse {
             D: unit_normal.dot(center),
         }
     }
+
+    /// 圆心在 center、法线方向为 normal 的圆盘
+    pub fn disk(center: Point3, normal: DVec3, radius: f64, mat: Arc<dyn Material>) -> Ellipse {
+        let (a, b) = normal.normalize().any_orthonormal_pair();
+        Ellipse::new(center, radius * a, radius * b, mat)
+    }
 }
 
 
@@ -314,6 +451,10 @@ impl Shape for Ellipse {
         }
         Some((alpha / 2.0 + 0.5, beta / 2.0 + 0.5))
     }
+
+    fn uv_tangents(&self) -> (DVec3, DVec3) {
+        (2.0 * self.get_u(), 2.0 * self.get_v())
+    }
 }
 
 
@@ -408,4 +549,85 @@ impl Shape for Annulus {
         }
         Some((alpha / 2.0 + 0.5, beta / 2.0 + 0.5))
     }
-}
\ No newline at end of file
+
+    fn uv_tangents(&self) -> (DVec3, DVec3) {
+        (2.0 * self.get_u(), 2.0 * self.get_v())
+    }
+}
+
+
+/// 经过 Q、由 u, v 张成的无限大平面，纹理沿 u, v 方向每隔一个向量长度重复一次。
+/// 包围盒是无穷大，最好不要放进 BVH
+pub struct Plane {
+    q: Point3,
+    u: DVec3,
+    v: DVec3,
+    w: DVec3,
+    mat: Arc<dyn Material>,
+    unit_normal: DVec3,
+    d: f64, // 平面方程的常数项
+}
+
+impl Plane {
+    pub fn new(q: Point3, u: DVec3, v: DVec3, mat: Arc<dyn Material>) -> Plane {
+        let n = u.cross(v);
+        let unit_normal = n.normalize();
+        let w = n / n.length_squared();
+        Plane {
+            q,
+            u,
+            v,
+            w,
+            mat,
+            unit_normal,
+            d: unit_normal.dot(q),
+        }
+    }
+}
+
+impl Hittable for Plane {
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::UNIVERSE
+    }
+    
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        <Self as Shape>::hit(self, r, ray_t)
+    }
+}
+
+impl Shape for Plane {
+    fn get_normal(&self) -> DVec3 {
+        self.unit_normal
+    }
+
+    fn get_w(&self) -> DVec3 {
+        self.w
+    }
+
+    #[allow(non_snake_case)]
+    fn get_D(&self) -> f64 {
+        self.d
+    }
+
+    #[allow(non_snake_case)]
+    fn get_Q(&self) -> Point3 {
+        self.q
+    }
+
+    fn get_u(&self) -> DVec3 {
+        self.u
+    }
+
+    fn get_v(&self) -> DVec3 {
+        self.v
+    }
+
+    fn get_mat_clone(&self) -> Arc<dyn Material> {
+        self.mat.clone()
+    }
+    
+    fn alpha_beta_hit_uv(&self, alpha: f64, beta: f64) -> Option<(f64, f64)> {
+        Some((alpha.rem_euclid(1.0), beta.rem_euclid(1.0)))
+    }
+}
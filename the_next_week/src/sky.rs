@@ -0,0 +1,200 @@
+use std::f64::consts::PI;
+
+use glam::DVec3;
+
+use crate::color::Color;
+use crate::constant;
+use crate::environment::EnvironmentMap;
+use crate::light::DirectionalLight;
+
+/// Preetham 日光天空模型，用太阳高度角、方位角和大气浑浊度生成天空亮度分布，
+/// 地平线以下用 ground_albedo 反射天空和太阳光近似地面。
+/// 天空烘焙成 EnvironmentMap 做重要性采样，太阳本身作为 DirectionalLight 单独加到光源列表里，给漫反射表面做直接采样；
+/// 有一定张角的太阳圆盘也烘焙进环境贴图，但只给直接看到它的光线（相机、镜面反射）用，避免重复计算
+pub struct PhysicalSky {
+    sun_direction: DVec3, // 指向太阳的单位向量
+    sun_theta: f64,       // 太阳天顶角
+    perez_y: [f64; 5],    // 亮度 Y 和色度 x, y 的 Perez 分布系数 A..E
+    perez_x: [f64; 5],
+    perez_yc: [f64; 5],
+    zenith: DVec3, // 天顶的 (Y, x, y)
+    sun_irradiance: Color,
+    sun_half_angle: f64, // 太阳圆盘的半张角，弧度
+    ground_radiance: Color,
+}
+
+impl PhysicalSky {
+    /// 角度都是角度制。方位角 0° 指向 +z，90° 指向 +x；turbidity 一般取 2（晴朗）到 10（雾霾）
+    pub fn new(sun_elevation: f64, sun_azimuth: f64, turbidity: f64, ground_albedo: Color) -> PhysicalSky {
+        let elevation = sun_elevation.clamp(0.0, 90.0).to_radians();
+        let azimuth = sun_azimuth.to_radians();
+        let sun_direction = DVec3::new(elevation.cos() * azimuth.sin(), elevation.sin(), elevation.cos() * azimuth.cos());
+        let sun_theta = PI / 2.0 - elevation;
+        let t = turbidity;
+
+        let perez_y = [0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251, 0.1206 * t - 2.5771, -0.0670 * t + 0.3703];
+        let perez_x = [-0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125, -0.0641 * t - 0.8989, -0.0033 * t + 0.0452];
+        let perez_yc = [-0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102, -0.0441 * t - 1.6537, -0.0109 * t + 0.0529];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * sun_theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let theta = [sun_theta.powi(3), sun_theta.powi(2), sun_theta, 1.0];
+        let zenith_chromaticity = |m: [[f64; 4]; 3]| {
+            let row = |r: [f64; 4]| r.iter().zip(theta.iter()).map(|(a, b)| a * b).sum::<f64>();
+            t * t * row(m[0]) + t * row(m[1]) + row(m[2])
+        };
+        let zenith_x = zenith_chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = zenith_chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let mut sky = PhysicalSky {
+            sun_direction,
+            sun_theta,
+            perez_y,
+            perez_x,
+            perez_yc,
+            zenith: DVec3::new(zenith_luminance.max(0.0), zenith_x, zenith_y),
+            sun_irradiance: PhysicalSky::compute_sun_irradiance(sun_theta, turbidity),
+            sun_half_angle: 0.5 * constant::SUN_ANGULAR_DIAMETER.to_radians(),
+            ground_radiance: Color::ZERO,
+        };
+
+        // 地面是漫反射平面：接收太阳直射和整个天空半球的光照
+        let irradiance = sky.sun_irradiance * sun_direction.y + sky.sky_irradiance();
+        sky.ground_radiance = ground_albedo * irradiance / PI;
+        sky
+    }
+
+    /// 太阳圆盘的张角（角度制），只影响镜面反射里太阳的大小，总辐照度不变
+    pub fn with_sun_angular_diameter(mut self, angular_diameter: f64) -> PhysicalSky {
+        self.sun_half_angle = 0.5 * angular_diameter.clamp(0.01, 90.0).to_radians();
+        self
+    }
+
+    /// 和天空配套的太阳光
+    pub fn sun_light(&self) -> DirectionalLight {
+        DirectionalLight::new(-self.sun_direction, self.sun_irradiance)
+    }
+
+    /// 太阳圆盘上的亮度，圆盘的立体角乘上它等于 sun_irradiance
+    fn sun_radiance(&self) -> Color {
+        let solid_angle = 2.0 * PI * (1.0 - self.sun_half_angle.cos());
+        self.sun_irradiance / solid_angle
+    }
+
+    /// 沿 direction 方向看到的天空（不含太阳本身）
+    pub fn radiance(&self, direction: DVec3) -> Color {
+        let d = direction.normalize();
+        if d.y < 0.0 {
+            return self.ground_radiance;
+        }
+        let cos_theta = d.y.max(constant::SKY_HORIZON_COS);
+        let gamma = d.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+
+        let luminance = self.zenith.x * perez(self.perez_y, cos_theta, gamma) / perez(self.perez_y, 1.0, self.sun_theta);
+        let x = self.zenith.y * perez(self.perez_x, cos_theta, gamma) / perez(self.perez_x, 1.0, self.sun_theta);
+        let y = self.zenith.z * perez(self.perez_yc, cos_theta, gamma) / perez(self.perez_yc, 1.0, self.sun_theta);
+        constant::SKY_RADIANCE_SCALE * xyy_to_linear_srgb(x, y, luminance).max(Color::ZERO)
+    }
+
+    /// 烘焙成等距柱状投影的环境贴图，交给 Camera::environment 做重要性采样。
+    /// 太阳圆盘单独烘焙成 visible_only 一层，按每个像素被圆盘覆盖的比例取亮度，总能量和 sun_light 一致
+    pub fn to_environment_map(&self, width: usize, height: usize) -> EnvironmentMap {
+        // 与 EnvironmentMap 的约定一致：第 0 行是正上方，u 从 -x 方向开始
+        let direction_of = |s: f64, t: f64| {
+            let phi = 2.0 * PI * s / width as f64;
+            let theta = PI * (1.0 - t / height as f64);
+            DVec3::new(-phi.cos() * theta.sin(), -theta.cos(), phi.sin() * theta.sin())
+        };
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for j in 0..height {
+            for i in 0..width {
+                pixels.push(self.radiance(direction_of(i as f64 + 0.5, j as f64 + 0.5)));
+            }
+        }
+
+        // 离太阳足够远的像素不可能碰到圆盘，跳过；其余像素内部按 n x n 均匀取点估计覆盖比例
+        let n = constant::SUN_DISK_SUPERSAMPLES;
+        let pixel_angle = 2.0 * PI / width as f64 + PI / height as f64;
+        let cos_margin = (self.sun_half_angle + pixel_angle).min(PI).cos();
+        let cos_half_angle = self.sun_half_angle.cos();
+        let sun_radiance = self.sun_radiance();
+        let mut sun_disk = vec![Color::ZERO; width * height];
+        for j in 0..height {
+            for i in 0..width {
+                if direction_of(i as f64 + 0.5, j as f64 + 0.5).dot(self.sun_direction) < cos_margin {
+                    continue;
+                }
+                let mut covered = 0;
+                for sj in 0..n {
+                    for si in 0..n {
+                        let s = i as f64 + (si as f64 + 0.5) / n as f64;
+                        let t = j as f64 + (sj as f64 + 0.5) / n as f64;
+                        if direction_of(s, t).dot(self.sun_direction) >= cos_half_angle {
+                            covered += 1;
+                        }
+                    }
+                }
+                sun_disk[j * width + i] = sun_radiance * covered as f64 / (n * n) as f64;
+            }
+        }
+
+        EnvironmentMap::new_from_pixels(width, height, pixels, 0.0, 1.0).with_visible_only(sun_disk)
+    }
+
+    // 太阳光穿过大气后的辐照度，按相对大气质量计算瑞利散射和气溶胶（Ångström 公式）的透射率，
+    // 三个通道分别取 680nm、550nm、440nm
+    fn compute_sun_irradiance(sun_theta: f64, turbidity: f64) -> Color {
+        let theta_degrees = sun_theta.to_degrees();
+        let air_mass = 1.0 / (sun_theta.cos() + 0.15 * (93.885 - theta_degrees).max(0.01).powf(-1.253));
+        let beta = 0.04608 * turbidity - 0.04586;
+        let transmittance = |lambda: f64| {
+            let rayleigh = 0.008569 * lambda.powi(-4) * (1.0 + 0.0113 * lambda.powi(-2) + 0.00013 * lambda.powi(-4));
+            let aerosol = beta * lambda.powf(-1.3);
+            (-air_mass * (rayleigh + aerosol)).exp()
+        };
+        constant::SUN_IRRADIANCE_SCALE * Color::new(transmittance(0.68), transmittance(0.55), transmittance(0.44))
+    }
+
+    // 天空半球在水平面上的辐照度 ∫ L cosθ dω，粗略数值积分
+    fn sky_irradiance(&self) -> Color {
+        let (n_theta, n_phi) = (16, 32);
+        let d_theta = PI / 2.0 / n_theta as f64;
+        let d_phi = 2.0 * PI / n_phi as f64;
+        let mut sum = Color::ZERO;
+        for j in 0..n_theta {
+            let theta = (j as f64 + 0.5) * d_theta;
+            for i in 0..n_phi {
+                let phi = (i as f64 + 0.5) * d_phi;
+                let direction = DVec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+                sum += self.radiance(direction) * theta.cos() * theta.sin() * d_theta * d_phi;
+            }
+        }
+        sum
+    }
+}
+
+// Perez 天空分布函数 F(θ, γ)
+fn perez(coefficients: [f64; 5], cos_theta: f64, gamma: f64) -> f64 {
+    let [a, b, c, d, e] = coefficients;
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+// CIE xyY 转线性 sRGB
+fn xyy_to_linear_srgb(x: f64, y: f64, luminance: f64) -> Color {
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+    Color::new(
+        3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z,
+        -0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z,
+        0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z,
+    )
+}
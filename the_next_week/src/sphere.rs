@@ -4,6 +4,7 @@ use std::sync::Arc;
 use glam::DVec3;
 
 use crate::aabb::Aabb;
+use crate::constant;
 use crate::hit_record::HitRecord;
 use crate::hittable::Hittable;
 use crate::interval::Interval;
@@ -58,6 +59,14 @@ impl Sphere {
         let phi = (-p.z).atan2(p.x) + PI;
         (phi / (2.0 * PI), theta / PI)
     }
+
+    // p 是单位球上的点，对 get_sphere_uv 求导得到 (dp/du, dp/dv)
+    fn get_sphere_tangents(&self, p: Point3) -> (DVec3, DVec3) {
+        let sin_theta = (1.0 - p.y * p.y).max(0.0).sqrt().max(constant::NEAR_ZERO_THRESHOLD);
+        let dpdu = 2.0 * PI * self.radius * DVec3::new(p.z, 0.0, -p.x);
+        let dpdv = PI * self.radius * DVec3::new(-p.x * p.y / sin_theta, sin_theta, -p.z * p.y / sin_theta);
+        (dpdu, dpdv)
+    }
 }
 
 impl Hittable for Sphere {
@@ -84,7 +93,8 @@ impl Hittable for Sphere {
         let p = r.at(root);
         let outward_normal = (p - current_center) / self.radius; // 单位化
         let (u, v) = Sphere::get_sphere_uv(outward_normal);
-        let rec = HitRecord::with_hit_data(root, p, (u, v), r, outward_normal, self.mat.clone());
+        let (dpdu, dpdv) = self.get_sphere_tangents(outward_normal);
+        let rec = HitRecord::with_hit_data(root, p, (u, v), r, outward_normal, self.mat.clone()).with_tangents(dpdu, dpdv);
         Some(rec)
     }
 
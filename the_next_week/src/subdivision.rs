@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::enums::SubdivisionScheme;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+use crate::interval::Interval;
+use crate::material::Material;
+use crate::point3::Point3;
+use crate::ray::Ray;
+use crate::shape::intersect_triangle;
+
+/// 多边形控制网格，细分若干次后拆成三角形渲染，粗糙的笼子也能变成光滑的曲面。
+/// 只被一个面使用的边是边界，边界按三次 B 样条曲线细分，保持不收缩
+#[derive(Clone)]
+pub struct ControlMesh {
+    positions: Vec<Point3>,
+    faces: Vec<Vec<usize>>, // 每个面的顶点下标，从外面看按逆时针排列
+}
+
+struct Edge {
+    vertices: (usize, usize),
+    faces: Vec<usize>,
+}
+
+impl Edge {
+    /// 非流形的边（三个以上的面共用）也当作边界处理
+    fn is_boundary(&self) -> bool {
+        self.faces.len() != 2
+    }
+
+    fn other(&self, v: usize) -> usize {
+        if self.vertices.0 == v { self.vertices.1 } else { self.vertices.0 }
+    }
+}
+
+/// 边以及顶点和边、面的邻接关系
+struct Topology {
+    edges: Vec<Edge>,
+    index: HashMap<(usize, usize), usize>,
+    vertex_edges: Vec<Vec<usize>>,
+    vertex_faces: Vec<Vec<usize>>,
+}
+
+impl Topology {
+    fn new(mesh: &ControlMesh) -> Topology {
+        let mut topology = Topology {
+            edges: Vec::new(),
+            index: HashMap::new(),
+            vertex_edges: vec![Vec::new(); mesh.positions.len()],
+            vertex_faces: vec![Vec::new(); mesh.positions.len()],
+        };
+        for (f, face) in mesh.faces.iter().enumerate() {
+            for (i, &a) in face.iter().enumerate() {
+                let b = face[(i + 1) % face.len()];
+                let next = topology.edges.len();
+                let e = *topology.index.entry(edge_key(a, b)).or_insert(next);
+                if e == next {
+                    topology.edges.push(Edge { vertices: (a, b), faces: Vec::new() });
+                    topology.vertex_edges[a].push(e);
+                    topology.vertex_edges[b].push(e);
+                }
+                topology.edges[e].faces.push(f);
+                topology.vertex_faces[a].push(f);
+            }
+        }
+        topology
+    }
+
+    fn edge(&self, a: usize, b: usize) -> usize {
+        self.index[&edge_key(a, b)]
+    }
+
+    /// 边界上的顶点只受相邻的两个边界顶点影响。位于边界上时返回新位置，
+    /// 边界在这里分叉或者断开时当作尖角，保持不动
+    fn boundary_vertex(&self, positions: &[Point3], v: usize) -> Option<Point3> {
+        let neighbors: Vec<usize> = self.vertex_edges[v]
+            .iter()
+            .map(|&e| &self.edges[e])
+            .filter(|edge| edge.is_boundary())
+            .map(|edge| edge.other(v))
+            .collect();
+        match neighbors.len() {
+            0 => None,
+            2 => Some(0.75 * positions[v] + 0.125 * (positions[neighbors[0]] + positions[neighbors[1]])),
+            _ => Some(positions[v]),
+        }
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl ControlMesh {
+    pub fn new(positions: Vec<Point3>, faces: Vec<Vec<usize>>) -> ControlMesh {
+        ControlMesh { positions, faces }
+    }
+
+    /// 按 scheme 细分 levels 次，每次面数变为原来的 4 倍左右
+    pub fn subdivide(&self, scheme: SubdivisionScheme, levels: usize) -> ControlMesh {
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = match scheme {
+                SubdivisionScheme::CatmullClark => mesh.catmull_clark(),
+                SubdivisionScheme::Loop => mesh.loop_subdivision(),
+            };
+        }
+        mesh
+    }
+
+    /// 拆成三角形，顶点法线取相邻面法线按面积加权的平均，三角形内部插值
+    pub fn triangulate(&self, mat: Arc<dyn Material>) -> HittableList {
+        let triangles = self.triangles();
+        let mut normals = vec![DVec3::ZERO; self.positions.len()];
+        for &[a, b, c] in &triangles {
+            let n = (self.positions[b] - self.positions[a]).cross(self.positions[c] - self.positions[a]);
+            normals[a] += n;
+            normals[b] += n;
+            normals[c] += n;
+        }
+
+        let mut list = HittableList::new();
+        for [a, b, c] in triangles {
+            list.add(Arc::new(SmoothTri::new(
+                [self.positions[a], self.positions[b], self.positions[c]],
+                [normals[a], normals[b], normals[c]].map(|n| n.normalize_or_zero()),
+                mat.clone(),
+            )));
+        }
+        list
+    }
+
+    /// 多边形按扇形拆成三角形
+    fn triangles(&self) -> Vec<[usize; 3]> {
+        self.faces
+            .iter()
+            .flat_map(|face| (1..face.len().saturating_sub(1)).map(move |i| [face[0], face[i], face[i + 1]]))
+            .collect()
+    }
+
+    /// 新顶点依次是原顶点、边点、面点，每个 n 边形分成 n 个四边形
+    fn catmull_clark(&self) -> ControlMesh {
+        let topology = Topology::new(self);
+        let positions = &self.positions;
+
+        let face_points: Vec<Point3> = self
+            .faces
+            .iter()
+            .map(|face| face.iter().map(|&v| positions[v]).sum::<DVec3>() / face.len() as f64)
+            .collect();
+
+        // 内部的边点是两个端点和两侧面点的平均
+        let edge_points: Vec<Point3> = topology
+            .edges
+            .iter()
+            .map(|edge| {
+                let midpoint = 0.5 * (positions[edge.vertices.0] + positions[edge.vertices.1]);
+                if edge.is_boundary() {
+                    midpoint
+                } else {
+                    0.5 * midpoint + 0.25 * (face_points[edge.faces[0]] + face_points[edge.faces[1]])
+                }
+            })
+            .collect();
+
+        // 内部顶点移到 (F + 2R + (n - 3)P) / n，F、R 是相邻面点、相邻边中点的平均，n 是度数
+        let vertex_points: Vec<Point3> = (0..positions.len())
+            .map(|v| {
+                if let Some(p) = topology.boundary_vertex(positions, v) {
+                    return p;
+                }
+                let (edges, faces) = (&topology.vertex_edges[v], &topology.vertex_faces[v]);
+                if faces.is_empty() {
+                    return positions[v];
+                }
+                let n = edges.len() as f64;
+                let f = faces.iter().map(|&f| face_points[f]).sum::<DVec3>() / faces.len() as f64;
+                let r = edges
+                    .iter()
+                    .map(|&e| 0.5 * (positions[v] + positions[topology.edges[e].other(v)]))
+                    .sum::<DVec3>()
+                    / n;
+                (f + 2.0 * r + (n - 3.0) * positions[v]) / n
+            })
+            .collect();
+
+        let (edge_base, face_base) = (positions.len(), positions.len() + edge_points.len());
+        let mut faces = Vec::new();
+        for (f, face) in self.faces.iter().enumerate() {
+            let k = face.len();
+            for i in 0..k {
+                let (prev, cur, next) = (face[(i + k - 1) % k], face[i], face[(i + 1) % k]);
+                faces.push(vec![
+                    cur,
+                    edge_base + topology.edge(cur, next),
+                    face_base + f,
+                    edge_base + topology.edge(prev, cur),
+                ]);
+            }
+        }
+
+        let positions = [vertex_points, edge_points, face_points].concat();
+        ControlMesh { positions, faces }
+    }
+
+    /// 新顶点依次是原顶点、边点，每个三角形分成 4 个
+    fn loop_subdivision(&self) -> ControlMesh {
+        let mesh = ControlMesh::new(self.positions.clone(), self.triangles().into_iter().map(Vec::from).collect());
+        let topology = Topology::new(&mesh);
+        let positions = &mesh.positions;
+
+        // 内部的边点是 3/8 的两个端点加 1/8 的两个对角顶点
+        let edge_points: Vec<Point3> = topology
+            .edges
+            .iter()
+            .map(|edge| {
+                let (a, b) = edge.vertices;
+                if edge.is_boundary() {
+                    return 0.5 * (positions[a] + positions[b]);
+                }
+                let opposite = edge
+                    .faces
+                    .iter()
+                    .map(|&f| mesh.faces[f].iter().copied().find(|&v| v != a && v != b).unwrap())
+                    .map(|v| positions[v])
+                    .sum::<DVec3>();
+                0.375 * (positions[a] + positions[b]) + 0.125 * opposite
+            })
+            .collect();
+
+        // 内部顶点保留 1 - n * beta，剩下的平均分给 n 个相邻顶点
+        let vertex_points: Vec<Point3> = (0..positions.len())
+            .map(|v| {
+                if let Some(p) = topology.boundary_vertex(positions, v) {
+                    return p;
+                }
+                let edges = &topology.vertex_edges[v];
+                if edges.is_empty() {
+                    return positions[v];
+                }
+                let n = edges.len() as f64;
+                let beta = if edges.len() == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n) };
+                let neighbors = edges.iter().map(|&e| positions[topology.edges[e].other(v)]).sum::<DVec3>();
+                (1.0 - n * beta) * positions[v] + beta * neighbors
+            })
+            .collect();
+
+        let edge_base = positions.len();
+        let mut faces = Vec::new();
+        for face in &mesh.faces {
+            let (a, b, c) = (face[0], face[1], face[2]);
+            let ab = edge_base + topology.edge(a, b);
+            let bc = edge_base + topology.edge(b, c);
+            let ca = edge_base + topology.edge(c, a);
+            faces.extend([vec![a, ab, ca], vec![b, bc, ab], vec![c, ca, bc], vec![ab, bc, ca]]);
+        }
+
+        let positions = [vertex_points, edge_points].concat();
+        ControlMesh { positions, faces }
+    }
+}
+
+/// 带顶点法线的三角形，法线在三角形内部插值
+struct SmoothTri {
+    vertices: [Point3; 3],
+    normals: [DVec3; 3],
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl SmoothTri {
+    fn new(vertices: [Point3; 3], normals: [DVec3; 3], mat: Arc<dyn Material>) -> SmoothTri {
+        SmoothTri {
+            vertices,
+            normals,
+            mat,
+            bbox: Aabb::new_from_points_vec(vertices.to_vec()),
+        }
+    }
+}
+
+impl Hittable for SmoothTri {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let (t, beta, gamma) = intersect_triangle(r, ray_t, self.vertices)?;
+        let [a, b, c] = self.vertices;
+        let (edge1, edge2) = (b - a, c - a);
+        let [na, nb, nc] = self.normals;
+        let normal = ((1.0 - beta - gamma) * na + beta * nb + gamma * nc)
+            .try_normalize()
+            .unwrap_or_else(|| edge1.cross(edge2).normalize());
+        let rec = HitRecord::with_hit_data(t, r.at(t), (beta, gamma), r, normal, self.mat.clone()).with_tangents(edge1, edge2);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use glam::DVec3;
+
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::constant;
+use crate::dvec3::DVec3Ext;
+use crate::hit_record::HitRecord;
+use crate::hittable::Hittable;
+use crate::interval::Interval;
+use crate::material::{Dielectric, Material, ScatterRecord};
+use crate::random_number_generator::random;
+use crate::ray::Ray;
+
+/// 次表面散射物体：光线从 boundary 进入后，在内部按散射、吸收系数做随机游走，
+/// 直到从另一处折射穿出表面。用于皮肤、蜡、大理石。boundary 必须是封闭的，它自己的材质会被忽略，
+/// 构造形状时随便给一个材质即可
+pub struct Subsurface {
+    boundary: Arc<dyn Hittable>,
+    walk: Arc<dyn Material>,
+}
+
+impl Subsurface {
+    pub fn new(boundary: Arc<dyn Hittable>, sigma_s: Color, sigma_a: Color, refraction_index: f64) -> Subsurface {
+        let walk = Arc::new(RandomWalk {
+            boundary: boundary.clone(),
+            sigma_s,
+            sigma_t: sigma_s + sigma_a,
+            refraction_index,
+        });
+        Subsurface { boundary, walk }
+    }
+
+    /// 用平均自由程和单次散射反照率描述介质，更直观
+    pub fn new_from_mean_free_path(boundary: Arc<dyn Hittable>, mean_free_path: f64, albedo: Color, refraction_index: f64) -> Subsurface {
+        let sigma_t = 1.0 / mean_free_path;
+        let sigma_s = albedo * sigma_t;
+        Subsurface::new(boundary, sigma_s, Color::splat(sigma_t) - sigma_s, refraction_index)
+    }
+}
+
+impl Hittable for Subsurface {
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let mut rec = self.boundary.hit(r, ray_t)?;
+        rec.mat = self.walk.clone();
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+struct RandomWalk {
+    boundary: Arc<dyn Hittable>,
+    sigma_s: Color,
+    sigma_t: Color,
+    refraction_index: f64,
+}
+
+impl Material for RandomWalk {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<ScatterRecord> {
+        // 从内部打到表面（例如相机在物体里面），直接穿出
+        if !rec.front_face {
+            return Some(ScatterRecord::specular(Color::ONE, Ray::new_with_time(rec.p, r_in.direction(), r_in.time())));
+        }
+
+        let unit_r_in_direction = r_in.direction().normalize();
+        let cos_theta = (-unit_r_in_direction).dot(rec.unit_normal).min(1.0);
+        if Dielectric::reflectance(cos_theta, 1.0 / self.refraction_index) > random() {
+            let reflected = unit_r_in_direction.reflect(rec.unit_normal);
+            return Some(ScatterRecord::specular(Color::ONE, Ray::new_with_time(rec.p, reflected, r_in.time())));
+        }
+
+        // 按余弦分布漫射进入内部，之后用三通道平均的 sigma_t 采样自由程，
+        // 各通道按真实透射率与采样 pdf 之比修正权重
+        let sigma_bar = self.sigma_t.element_sum() / 3.0;
+        let mut throughput = Color::ONE;
+        let mut p = rec.p;
+        let mut direction = -rec.unit_normal + DVec3::random_unit();
+        if direction.near_zero() {
+            direction = -rec.unit_normal;
+        }
+
+        for _ in 0..constant::SUBSURFACE_MAX_STEPS {
+            let walk_ray = Ray::new_with_time(p, direction.normalize(), r_in.time());
+            let distance = -(1.0 - random()).ln() / sigma_bar;
+            let exit = self.boundary.hit(&walk_ray, Interval::new(constant::RAY_MIN_DISTANCE, f64::INFINITY));
+
+            match exit {
+                Some(exit_rec) if exit_rec.t <= distance => {
+                    throughput *= transmittance(self.sigma_t, exit_rec.t) / (-sigma_bar * exit_rec.t).exp();
+                    // 从内部打到表面，和进入时一样按 Fresnel 反射率决定折射出去还是反射回内部继续游走
+                    let cos_theta = (-walk_ray.direction()).dot(exit_rec.unit_normal).min(1.0);
+                    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                    let cannot_refract = self.refraction_index * sin_theta > 1.0;
+                    if cannot_refract || Dielectric::reflectance(cos_theta, self.refraction_index) > random() {
+                        p = exit_rec.p;
+                        direction = walk_ray.direction().reflect(exit_rec.unit_normal);
+                    } else {
+                        // 出射点不是这次的交点，光源直接采样帮不上忙，当成镜面处理
+                        let refracted = walk_ray.direction().refract(exit_rec.unit_normal, self.refraction_index);
+                        return Some(ScatterRecord::specular(throughput, Ray::new_with_time(exit_rec.p, refracted, r_in.time())));
+                    }
+                }
+                // 没有出口说明 boundary 不封闭或数值误差逃出了物体，当作被吸收
+                None => return None,
+                Some(_) => {
+                    throughput *= self.sigma_s * transmittance(self.sigma_t, distance) / (sigma_bar * (-sigma_bar * distance).exp());
+                    p = walk_ray.at(distance);
+                    direction = DVec3::random_unit();
+                }
+            }
+
+            // 权重太小时做俄罗斯轮盘赌，避免在吸收强的介质里空转
+            let survival = throughput.max_element().min(1.0);
+            if survival < 0.1 {
+                if random() > survival {
+                    return None;
+                }
+                throughput /= survival;
+            }
+        }
+        None
+    }
+}
+
+fn transmittance(sigma_t: Color, distance: f64) -> Color {
+    Color::new(
+        (-sigma_t.x * distance).exp(),
+        (-sigma_t.y * distance).exp(),
+        (-sigma_t.z * distance).exp(),
+    )
+}
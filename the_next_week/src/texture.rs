@@ -1,16 +1,27 @@
 use std::sync::Arc;
 
-use image::RgbImage;
+use glam::{DVec2, DVec4};
 
 use crate::color::Color;
-use crate::config;
-use crate::enums::NoiseType;
+use crate::constant;
+use crate::enums::{CellularFeature, ColorSpace, FilterMode, NoiseType, WrapMode};
 use crate::interval::Interval;
 use crate::perlin::Perlin;
 use crate::point3::Point3;
+use crate::texture_cache::{CachedImage, MipLevel, TextureCache};
 
 pub trait Texture: Send + Sync {
     fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+
+    /// footprint 是这次查询在纹理坐标上覆盖的宽度，需要抗锯齿的纹理可以据此做过滤，默认直接点采样
+    fn value_filtered(&self, u: f64, v: f64, p: Point3, _footprint: f64) -> Color {
+        self.value(u, v, p)
+    }
+
+    /// 不透明度，没有 alpha 通道的纹理都是完全不透明的
+    fn alpha(&self, _u: f64, _v: f64, _p: Point3) -> f64 {
+        1.0
+    }
 }
 
 pub struct SolidColor {
@@ -58,68 +69,377 @@ impl Texture for CheckerTexture {
     }
 }
 
+/// 纹理坐标空间（而不是世界空间）的棋盘格，u、v 方向各有 u_count、v_count 格
+pub struct UvCheckerTexture {
+    u_count: f64,
+    v_count: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl UvCheckerTexture {
+    pub fn new(u_count: f64, v_count: f64, c1: Color, c2: Color) -> UvCheckerTexture {
+        UvCheckerTexture {
+            u_count,
+            v_count,
+            even: Arc::new(SolidColor::new(c1)),
+            odd: Arc::new(SolidColor::new(c2)),
+        }
+    }
+}
+
+impl Texture for UvCheckerTexture {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let u_interger = (self.u_count * u).floor() as isize;
+        let v_interger = (self.v_count * v).floor() as isize;
+        if (u_interger + v_interger) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// 对任意纹理的纹理坐标做变换：先按 scale 缩放，再绕原点旋转 rotation 度，最后加上 offset，
+/// 变换结果按 wrap 处理后再交给内部纹理
+pub struct UvTransform {
+    tex: Arc<dyn Texture>,
+    scale: DVec2,
+    offset: DVec2,
+    sin_rotation: f64,
+    cos_rotation: f64,
+    wrap: WrapMode,
+}
+
+impl UvTransform {
+    pub fn new(tex: Arc<dyn Texture>, scale: DVec2, offset: DVec2, rotation: f64, wrap: WrapMode) -> UvTransform {
+        let radians = rotation.to_radians();
+        UvTransform {
+            tex,
+            scale,
+            offset,
+            sin_rotation: radians.sin(),
+            cos_rotation: radians.cos(),
+            wrap,
+        }
+    }
+}
+
+impl UvTransform {
+    fn transform(&self, u: f64, v: f64) -> Option<(f64, f64)> {
+        let scaled = self.scale * DVec2::new(u, v);
+        let rotated = DVec2::new(
+            self.cos_rotation * scaled.x - self.sin_rotation * scaled.y,
+            self.sin_rotation * scaled.x + self.cos_rotation * scaled.y,
+        );
+        let uv = rotated + self.offset;
+        Some((self.wrap.apply(uv.x)?, self.wrap.apply(uv.y)?))
+    }
+}
+
+impl Texture for UvTransform {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        match self.transform(u, v) {
+            Some((wrapped_u, wrapped_v)) => self.tex.value(wrapped_u, wrapped_v, p),
+            None => self.wrap.border_color(),
+        }
+    }
+
+    fn value_filtered(&self, u: f64, v: f64, p: Point3, footprint: f64) -> Color {
+        // 旋转不改变宽度，缩放按较大的方向放大
+        let scaled_footprint = footprint * self.scale.abs().max_element();
+        match self.transform(u, v) {
+            Some((wrapped_u, wrapped_v)) => self.tex.value_filtered(wrapped_u, wrapped_v, p, scaled_footprint),
+            None => self.wrap.border_color(),
+        }
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: Point3) -> f64 {
+        match self.transform(u, v) {
+            Some((wrapped_u, wrapped_v)) => self.tex.alpha(wrapped_u, wrapped_v, p),
+            None => 0.0, // 只有 Border 会走到这里，边框外透明
+        }
+    }
+}
+
+impl WrapMode {
+    /// 把纹理坐标映射回 [0, 1]，Border 模式下超出范围返回 None
+    pub fn apply(&self, x: f64) -> Option<f64> {
+        match self {
+            WrapMode::Repeat => Some(x - x.floor()),
+            WrapMode::Mirror => {
+                let t = x - 2.0 * (x / 2.0).floor(); // [0, 2)
+                Some(if t > 1.0 { 2.0 - t } else { t })
+            }
+            WrapMode::Clamp => Some(Interval::new(0.0, 1.0).clamp(x)),
+            WrapMode::Border(_) => Interval::new(0.0, 1.0).contains(x).then_some(x),
+        }
+    }
+
+    fn border_color(&self) -> Color {
+        match self {
+            WrapMode::Border(color) => *color,
+            _ => Color::ZERO,
+        }
+    }
+}
+
 pub struct ImageTexture {
-    data: RgbImage,
+    image: Arc<CachedImage>,
+    wrap: WrapMode,
+    filter: FilterMode,
 }
 
 impl ImageTexture {
     pub fn new(filename: &str) -> anyhow::Result<ImageTexture> {
-        let img = image::open(filename)?.into_rgb8();
-        Ok(ImageTexture { data: img })
+        ImageTexture::new_with_options(filename, WrapMode::Clamp, FilterMode::Bilinear, ColorSpace::Srgb)
+    }
+
+    /// 图片数据从全局缓存中取，同一文件按同样方式解码的纹理共享一份
+    pub fn new_with_options(filename: &str, wrap: WrapMode, filter: FilterMode, color_space: ColorSpace) -> anyhow::Result<ImageTexture> {
+        // 只有三线性过滤才会用到 mipmap，在第一次取样时从共享的原图生成
+        let image = TextureCache::global().load(filename, color_space)?;
+        Ok(ImageTexture { image, wrap, filter })
+    }
+
+    /// 按整数像素坐标取值，越界的坐标按 wrap 处理
+    fn texel(&self, level: &MipLevel, x: isize, y: isize) -> DVec4 {
+        let wrap_index = |index: isize, size: usize| -> Option<usize> {
+            let size = size as isize;
+            match self.wrap {
+                WrapMode::Repeat => Some(index.rem_euclid(size) as usize),
+                WrapMode::Mirror => {
+                    let t = index.rem_euclid(2 * size);
+                    Some(if t >= size { 2 * size - 1 - t } else { t } as usize)
+                }
+                WrapMode::Clamp => Some(index.clamp(0, size - 1) as usize),
+                WrapMode::Border(_) => (0..size).contains(&index).then_some(index as usize),
+            }
+        };
+        match (wrap_index(x, level.width), wrap_index(y, level.height)) {
+            (Some(i), Some(j)) => level.texel(i, j),
+            _ => self.wrap.border_color().extend(0.0),
+        }
+    }
+
+    fn nearest(&self, level: &MipLevel, u: f64, v: f64) -> DVec4 {
+        let x = (u * level.width as f64).floor() as isize;
+        let y = ((1.0 - v) * level.height as f64).floor() as isize;
+        self.texel(level, x, y)
+    }
+
+    fn bilinear(&self, level: &MipLevel, u: f64, v: f64) -> DVec4 {
+        // 像素中心位于 (i + 0.5, j + 0.5)
+        let x = u * level.width as f64 - 0.5;
+        let y = (1.0 - v) * level.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = (1.0 - tx) * self.texel(level, x0, y0) + tx * self.texel(level, x0 + 1, y0);
+        let bottom = (1.0 - tx) * self.texel(level, x0, y0 + 1) + tx * self.texel(level, x0 + 1, y0 + 1);
+        (1.0 - ty) * top + ty * bottom
+    }
+
+    fn bicubic(&self, level: &MipLevel, u: f64, v: f64) -> DVec4 {
+        // Catmull-Rom 权重，t 是到左侧第二个像素的小数偏移
+        let weights = |t: f64| -> [f64; 4] {
+            let t2 = t * t;
+            let t3 = t2 * t;
+            [
+                0.5 * (-t3 + 2.0 * t2 - t),
+                0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+                0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+                0.5 * (t3 - t2),
+            ]
+        };
+        let x = u * level.width as f64 - 0.5;
+        let y = (1.0 - v) * level.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (wx, wy) = (weights(x - x0), weights(y - y0));
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let mut texel = DVec4::ZERO;
+        for (dy, weight_y) in wy.iter().enumerate() {
+            let mut row = DVec4::ZERO;
+            for (dx, weight_x) in wx.iter().enumerate() {
+                row += *weight_x * self.texel(level, x0 + dx as isize - 1, y0 + dy as isize - 1);
+            }
+            texel += *weight_y * row;
+        }
+        // Catmull-Rom 会过冲，夹回非负，alpha 不超过 1
+        texel.max(DVec4::ZERO).min(DVec4::new(f64::INFINITY, f64::INFINITY, f64::INFINITY, 1.0))
+    }
+
+    fn trilinear(&self, u: f64, v: f64, footprint: f64) -> DVec4 {
+        let base = self.image.base();
+        let texel_footprint = footprint * base.width.max(base.height) as f64;
+        if texel_footprint <= 1.0 {
+            return self.bilinear(base, u, v);
+        }
+
+        // 第 0 层是原图，之后是下采样得到的各层
+        let mips = self.image.mip_levels();
+        let level = |index: usize| if index == 0 { base } else { &mips[index - 1] };
+        let lod = texel_footprint.log2().min(mips.len() as f64);
+        let lower = lod.floor() as usize;
+        let upper = (lower + 1).min(mips.len());
+        let t = lod - lower as f64;
+        (1.0 - t) * self.bilinear(level(lower), u, v) + t * self.bilinear(level(upper), u, v)
+    }
+
+    /// 按过滤方式取出 rgba
+    fn sample(&self, u: f64, v: f64, footprint: f64) -> DVec4 {
+        // Border 以外的模式在取像素时处理越界，这里只需要判断是否落在边框上
+        if let WrapMode::Border(color) = self.wrap
+            && (!Interval::new(0.0, 1.0).contains(u) || !Interval::new(0.0, 1.0).contains(v))
+        {
+            return color.extend(0.0);
+        }
+
+        let base = self.image.base();
+        match self.filter {
+            FilterMode::Nearest => self.nearest(base, u, v),
+            FilterMode::Bilinear => self.bilinear(base, u, v),
+            FilterMode::Bicubic => self.bicubic(base, u, v),
+            FilterMode::Trilinear => self.trilinear(u, v, footprint),
+        }
     }
 }
 
 impl Texture for ImageTexture {
-    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
-        let itv = Interval::new(0.0, 1.0);
-        let cord_u = itv.clamp(u);
-        let cord_v = 1.0 - itv.clamp(v);
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        self.value_filtered(u, v, p, 0.0)
+    }
+
+    fn value_filtered(&self, u: f64, v: f64, _p: Point3, footprint: f64) -> Color {
+        self.sample(u, v, footprint).truncate()
+    }
 
-        let i = (cord_u * self.data.width() as f64) as u32;
-        let j = (cord_v * self.data.height() as f64) as u32;
-        let pixel = self.data.get_pixel(i, j);
-        let color_scale = 1.0 / 255.0;
-        Color::new(
-            color_scale * pixel[0] as f64,
-            color_scale * pixel[1] as f64,
-            color_scale * pixel[2] as f64,
-        )
+    fn alpha(&self, u: f64, v: f64, _p: Point3) -> f64 {
+        self.sample(u, v, 0.0).w
+    }
+}
+
+/// 把内部纹理的 alpha 通道当作灰度输出，可以直接作为 AlphaCutout 的 opacity
+pub struct AlphaChannel {
+    tex: Arc<dyn Texture>,
+}
+
+impl AlphaChannel {
+    pub fn new(tex: Arc<dyn Texture>) -> AlphaChannel {
+        AlphaChannel { tex }
+    }
+}
+
+impl Texture for AlphaChannel {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        Color::splat(self.tex.alpha(u, v, p))
+    }
+}
+
+/// 把 [0, 1] 的标量映射成颜色，stops 之间线性插值，两端之外取端点的颜色
+#[derive(Clone)]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f64, Color)>) -> ColorRamp {
+        assert!(!stops.is_empty(), "color ramp needs at least one stop");
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp { stops }
+    }
+
+    pub fn value(&self, t: f64) -> Color {
+        let upper = self.stops.partition_point(|(position, _)| *position <= t);
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        if upper == self.stops.len() {
+            return self.stops[upper - 1].1;
+        }
+        let (t0, c0) = self.stops[upper - 1];
+        let (t1, c1) = self.stops[upper];
+        c0.lerp(c1, (t - t0) / (t1 - t0))
     }
 }
 
 pub struct NoiseTexture {
     noise: Perlin,
+    noise_type: NoiseType,
     scale: f64,
+    octaves: Option<usize>, // 没有设置时 LatticeRandomVectors 只有一层，其他分形噪声用 constant::NOISE_OCTAVES 层
+    lacunarity: f64,
+    gain: f64,
+    ramp: Option<ColorRamp>, // 没有设置时输出灰度
 }
 
 impl NoiseTexture {
-    pub fn new(scale: f64) -> NoiseTexture {
+    pub fn new(noise_type: NoiseType, scale: f64, seed: u64) -> NoiseTexture {
         NoiseTexture {
-            noise: Perlin::new(),
+            noise: Perlin::new(seed),
+            noise_type,
             scale,
+            octaves: None,
+            lacunarity: constant::NOISE_LACUNARITY,
+            gain: constant::NOISE_GAIN,
+            ramp: None,
         }
     }
-}
 
-impl Texture for NoiseTexture {
-    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
-        let white = Color::ONE;
-        match config::NOISE_TYPE {
-            NoiseType::HashedRandom => {
-                white * self.noise.hash_random_noise(self.scale * p)
-            }
-            NoiseType::TrilinearInterpolation => {
-                white * self.noise.trilinear_interpolation_noise(self.scale * p)
-            }
-            NoiseType::LatticeRandomVectors => {
-                white * 0.5 * (1.0 + self.noise.lattice_random_vectors_noise(self.scale * p))
+    /// 分形叠加的参数，HashedRandom、TrilinearInterpolation 和 Worley 只有一层，不受影响。
+    /// LatticeRandomVectors 默认是单层的 Perlin 噪声，设置之后才改成 fBm
+    pub fn with_octaves(mut self, octaves: usize, lacunarity: f64, gain: f64) -> NoiseTexture {
+        self.octaves = Some(octaves);
+        self.lacunarity = lacunarity;
+        self.gain = gain;
+        self
+    }
+
+    pub fn with_color_ramp(mut self, ramp: ColorRamp) -> NoiseTexture {
+        self.ramp = Some(ramp);
+        self
+    }
+
+    fn scalar(&self, p: Point3) -> f64 {
+        let octaves = self.octaves.unwrap_or(constant::NOISE_OCTAVES);
+        let (lacunarity, gain) = (self.lacunarity, self.gain);
+        match self.noise_type {
+            NoiseType::HashedRandom => self.noise.hash_random_noise(self.scale * p),
+            NoiseType::TrilinearInterpolation => self.noise.trilinear_interpolation_noise(self.scale * p),
+            NoiseType::LatticeRandomVectors => match self.octaves {
+                Some(octaves) => 0.5 * (1.0 + self.noise.fbm(self.scale * p, octaves, lacunarity, gain)),
+                None => 0.5 * (1.0 + self.noise.lattice_random_vectors_noise(self.scale * p)),
+            },
+            NoiseType::Turbulence => self.noise.turb(self.scale * p, octaves, lacunarity, gain),
+            NoiseType::TurbulenceMarble => {
+                0.5 * (1.0 + (self.scale * p.z + 10.0 * self.noise.turb(p, octaves, lacunarity, gain)).sin())
             }
-            NoiseType::Turbulence => {
-                white * self.noise.turb(p, config::TURBULENCE_DEPTH)
+            NoiseType::Simplex => 0.5 * (1.0 + self.noise.simplex_fbm(self.scale * p, octaves, lacunarity, gain)),
+            NoiseType::Worley(feature) => {
+                // 距离本身可以超过 1，截断后和其他噪声一样落在 [0, 1]
+                let (f1, f2) = self.noise.worley_noise(self.scale * p);
+                let distance = match feature {
+                    CellularFeature::F1 => f1,
+                    CellularFeature::F2 => f2,
+                    CellularFeature::F2MinusF1 => f2 - f1,
+                };
+                distance.min(1.0)
             }
-            NoiseType::TurbulenceMarble => {
-                Color::splat(0.5) * (1.0 + (self.scale * p.z + 10.0 * self.noise.turb(p, config::TURBULENCE_DEPTH)).sin())
+            NoiseType::DomainWarped(strength) => {
+                0.5 * (1.0 + self.noise.domain_warped_fbm(self.scale * p, octaves, lacunarity, gain, strength))
             }
         }
     }
 }
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let t = self.scalar(p);
+        match &self.ramp {
+            Some(ramp) => ramp.value(t),
+            None => Color::splat(t),
+        }
+    }
+}
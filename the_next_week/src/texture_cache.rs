@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
+
+use glam::DVec4;
+use image::ColorType;
+
+use crate::config;
+use crate::enums::ColorSpace;
+
+/// 一层 mipmap 的像素，按原图的位深存放。8 位图片每个像素只占 4 字节，取值时再解码成线性的 DVec4
+enum Texels {
+    Srgb8(Vec<[u8; 4]>),   // sRGB 编码的颜色，查表解码
+    Linear8(Vec<[u8; 4]>), // 法线、粗糙度之类的数据贴图
+    Float(Vec<[f32; 4]>),  // 16 位整数和浮点图片，已经转换到线性空间
+}
+
+/// 一层 mipmap，像素按行存放，第 0 行是图片最上面一行
+pub struct MipLevel {
+    pub width: usize,
+    pub height: usize,
+    texels: Texels,
+}
+
+/// 256 个 8 位 sRGB 编码值对应的线性值
+static SRGB_TO_LINEAR: LazyLock<[f64; 256]> = LazyLock::new(|| std::array::from_fn(|i| srgb_to_linear(i as f32 / 255.0)));
+
+impl Texels {
+    fn memory_bytes(&self) -> usize {
+        match self {
+            Texels::Srgb8(texels) | Texels::Linear8(texels) => texels.len() * mem::size_of::<[u8; 4]>(),
+            Texels::Float(texels) => texels.len() * mem::size_of::<[f32; 4]>(),
+        }
+    }
+
+    /// xyz 是线性空间的颜色，w 是 alpha
+    fn get(&self, index: usize) -> DVec4 {
+        match self {
+            Texels::Srgb8(texels) => {
+                let [r, g, b, a] = texels[index];
+                DVec4::new(SRGB_TO_LINEAR[r as usize], SRGB_TO_LINEAR[g as usize], SRGB_TO_LINEAR[b as usize], a as f64 / 255.0)
+            }
+            Texels::Linear8(texels) => DVec4::from_array(texels[index].map(|c| c as f64 / 255.0)),
+            Texels::Float(texels) => DVec4::from_array(texels[index].map(|c| c as f64)),
+        }
+    }
+
+    /// 按同样的格式存放一组线性值，下采样后的 mipmap 和原图占用同样的位深
+    fn encode_like(&self, values: Vec<DVec4>) -> Texels {
+        let quantize = |x: f64| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match self {
+            Texels::Srgb8(_) => Texels::Srgb8(
+                values
+                    .iter()
+                    .map(|t| [quantize(linear_to_srgb(t.x)), quantize(linear_to_srgb(t.y)), quantize(linear_to_srgb(t.z)), quantize(t.w)])
+                    .collect(),
+            ),
+            Texels::Linear8(_) => Texels::Linear8(values.iter().map(|t| t.to_array().map(quantize)).collect()),
+            Texels::Float(_) => Texels::Float(values.iter().map(|t| t.as_vec4().to_array()).collect()),
+        }
+    }
+}
+
+impl MipLevel {
+    pub fn texel(&self, i: usize, j: usize) -> DVec4 {
+        self.texels.get(j * self.width + i)
+    }
+
+    /// 每 2 x 2 个像素在线性空间取平均得到下一层，奇数边长时最后一行/列重复使用
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut values = Vec::with_capacity(width * height);
+        for j in 0..height {
+            for i in 0..width {
+                let (x0, y0) = ((2 * i).min(self.width - 1), (2 * j).min(self.height - 1));
+                let (x1, y1) = ((2 * i + 1).min(self.width - 1), (2 * j + 1).min(self.height - 1));
+                let sum = self.texel(x0, y0) + self.texel(x1, y0) + self.texel(x0, y1) + self.texel(x1, y1);
+                values.push(sum / 4.0);
+            }
+        }
+        MipLevel {
+            width,
+            height,
+            texels: self.texels.encode_like(values),
+        }
+    }
+}
+
+/// 8 位 sRGB 编码转回线性值
+fn srgb_to_linear(encoded: f32) -> f64 {
+    let encoded = encoded as f64;
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(linear: f64) -> f64 {
+    if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// 同一个文件按不同的方式解码得到的数据不同，需要分开缓存。
+/// 是否需要 mipmap 不影响解码结果，原图由所有过滤方式共享
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct ImageKey {
+    path: PathBuf,
+    color_space: ColorSpace,
+}
+
+impl ImageKey {
+    /// 粗糙度、法线之类的数据贴图要用 ColorSpace::Linear 读取，不能做 sRGB 解码。
+    /// .hdr、.exr 等浮点格式本身就是线性的，忽略 color_space
+    fn decode(&self) -> anyhow::Result<MipLevel> {
+        let img = image::open(&self.path)?;
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        let texels = match img.color() {
+            // 8 位图片原样保存，alpha 永远是线性的
+            ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 => {
+                let texels = img.into_rgba8().pixels().map(|pixel| pixel.0).collect();
+                match self.color_space {
+                    ColorSpace::Srgb => Texels::Srgb8(texels),
+                    ColorSpace::Linear => Texels::Linear8(texels),
+                }
+            }
+            color => {
+                // 16 位整数会被归一化到 [0, 1]
+                let is_float = matches!(color, ColorType::Rgb32F | ColorType::Rgba32F);
+                let decode = |channel: f32| -> f32 {
+                    if self.color_space == ColorSpace::Srgb && !is_float {
+                        srgb_to_linear(channel) as f32
+                    } else {
+                        channel
+                    }
+                };
+                let img = img.into_rgba32f();
+                Texels::Float(img.pixels().map(|pixel| [decode(pixel[0]), decode(pixel[1]), decode(pixel[2]), pixel[3]]).collect())
+            }
+        };
+        Ok(MipLevel { width, height, texels })
+    }
+}
+
+/// 解码后的图片，由所有使用同一文件、同一解码方式的 ImageTexture 共享
+pub struct CachedImage {
+    key: ImageKey,
+    base: OnceLock<MipLevel>,
+    mips: OnceLock<Vec<MipLevel>>, // 原图之后的各层，每层长宽减半，直到 1 x 1，第一次三线性过滤时才生成
+}
+
+impl CachedImage {
+    /// 原图。延迟加载时第一次取像素才真正解码，文件在注册之后被改坏只能直接报错退出
+    pub fn base(&self) -> &MipLevel {
+        self.base.get_or_init(|| {
+            self.key
+                .decode()
+                .unwrap_or_else(|e| panic!("Failed to decode texture {}: {}", self.key.path.display(), e))
+        })
+    }
+
+    /// mip_levels()[0] 是原图下采样一次的结果，原图只有 1 x 1 时为空
+    pub fn mip_levels(&self) -> &[MipLevel] {
+        self.mips.get_or_init(|| {
+            let base = self.base();
+            let mut levels: Vec<MipLevel> = Vec::new();
+            loop {
+                let last = levels.last().unwrap_or(base);
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+                let next = last.downsample();
+                levels.push(next);
+            }
+            levels
+        })
+    }
+
+    fn memory_bytes(&self) -> usize {
+        let base = self.base.get().map_or(0, |level| level.texels.memory_bytes());
+        let mips: usize = self.mips.get().map_or(0, |levels| levels.iter().map(|level| level.texels.memory_bytes()).sum());
+        base + mips
+    }
+}
+
+/// 按路径和解码方式缓存图片，重复使用同一张大图时只解码一次
+pub struct TextureCache {
+    images: Mutex<HashMap<ImageKey, Arc<CachedImage>>>,
+}
+
+static TEXTURE_CACHE: LazyLock<TextureCache> = LazyLock::new(|| TextureCache {
+    images: Mutex::new(HashMap::new()),
+});
+
+impl TextureCache {
+    pub fn global() -> &'static TextureCache {
+        &TEXTURE_CACHE
+    }
+
+    pub fn load(&self, filename: &str, color_space: ColorSpace) -> anyhow::Result<Arc<CachedImage>> {
+        // 规范化路径，不同写法指向同一个文件时也能命中；文件不存在时在这里就报错
+        let key = ImageKey {
+            path: fs::canonicalize(filename)?,
+            color_space,
+        };
+
+        let mut images = self.images.lock().expect("texture cache poisoned");
+        if let Some(image) = images.get(&key) {
+            return Ok(Arc::clone(image));
+        }
+
+        let base = if config::LAZY_TEXTURE_LOADING {
+            OnceLock::new()
+        } else {
+            OnceLock::from(key.decode()?)
+        };
+        let image = Arc::new(CachedImage {
+            key: key.clone(),
+            base,
+            mips: OnceLock::new(),
+        });
+        images.insert(key, Arc::clone(&image));
+        Ok(image)
+    }
+
+    /// 打印缓存的图片数量、被共享的次数和已解码数据占用的内存
+    pub fn print_statistics(&self) {
+        let images = self.images.lock().expect("texture cache poisoned");
+        if images.is_empty() {
+            return;
+        }
+
+        let decoded = images.values().filter(|image| image.base.get().is_some()).count();
+        // 缓存本身持有一份引用
+        let references: usize = images.values().map(|image| Arc::strong_count(image) - 1).sum();
+        let bytes: usize = images.values().map(|image| image.memory_bytes()).sum();
+        println!(
+            "Textures: {} images ({} decoded), {} references, {:.1} MiB.",
+            images.len(),
+            decoded,
+            references,
+            bytes as f64 / (1024.0 * 1024.0)
+        );
+    }
+}
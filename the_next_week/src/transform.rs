@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use crate::aabb::Aabb;
+use crate::color::Color;
 use crate::hit_record::HitRecord;
 use crate::hittable::Hittable;
 use crate::interval::Interval;
@@ -40,6 +41,11 @@ impl Hittable for Translate {
             None
         }
     }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        let offset_r = Ray::new_with_time(r.origin() - self.offset, r.direction(), r.time());
+        self.object.transmittance(&offset_r, ray_t)
+    }
 }
 
 pub struct RotateY {
@@ -60,14 +66,9 @@ impl RotateY {
             bbox,
         }
     }
-}
-
-impl Hittable for RotateY {
-    fn bounding_box(&self) -> Aabb {
-        self.bbox
-    }
 
-    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+    /// 把世界空间的光线反向旋转 theta 到物体空间
+    fn to_object_space(&self, r: &Ray) -> Ray {
         let origin = r.origin();
         let direction = r.direction();
         // 反向旋转 theta
@@ -81,7 +82,17 @@ impl Hittable for RotateY {
             direction.y,
             self.sin_theta * direction.x + self.cos_theta * direction.z,
         );
-        let rotated_ray = Ray::new_with_time(new_origin, new_direction, r.time());
+        Ray::new_with_time(new_origin, new_direction, r.time())
+    }
+}
+
+impl Hittable for RotateY {
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn hit(&self, r: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let rotated_ray = self.to_object_space(r);
         if let Some(mut rec) = self.object.hit(&rotated_ray, ray_t) {
             rec.p = Point3::new(
                 self.cos_theta * rec.p.x + self.sin_theta * rec.p.z,
@@ -93,9 +104,23 @@ impl Hittable for RotateY {
                 rec.unit_normal.y,
                 -self.sin_theta * rec.unit_normal.x + self.cos_theta * rec.unit_normal.z,
             );
+            rec.dpdu = DVec3::new(
+                self.cos_theta * rec.dpdu.x + self.sin_theta * rec.dpdu.z,
+                rec.dpdu.y,
+                -self.sin_theta * rec.dpdu.x + self.cos_theta * rec.dpdu.z,
+            );
+            rec.dpdv = DVec3::new(
+                self.cos_theta * rec.dpdv.x + self.sin_theta * rec.dpdv.z,
+                rec.dpdv.y,
+                -self.sin_theta * rec.dpdv.x + self.cos_theta * rec.dpdv.z,
+            );
             Some(rec)
         } else {
             None
         }
     }
+
+    fn transmittance(&self, r: &Ray, ray_t: Interval) -> Color {
+        self.object.transmittance(&self.to_object_space(r), ray_t)
+    }
 }